@@ -0,0 +1,641 @@
+#![deny(clippy::all)]
+
+use argh::FromArgs;
+use miinaharava::{
+    game::{Game, GameWindow},
+    history::{Action, History},
+    minefield::{
+        Coord, CustomMinefield, GameState, GenerationPolicy, Minefield, MinefieldError, Topology,
+        CUSTOM_MAX_SIZE,
+    },
+    replay::{Replay, ReplayPlayer},
+    sdl2::{event::Event, keyboard::Keycode, mouse::MouseButton},
+    statistics::{ClickStats, Statistics},
+};
+use minesweeper_ai::ai::no_guess::{generate_no_guess, NoGuessMinefield};
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(Clone, Copy, Debug)]
+enum Difficulty {
+    Easy,
+    Intermediate,
+    Expert,
+    Custom {
+        width: u16,
+        height: u16,
+        mines: u16,
+    },
+    /// The board loaded with `--board`.
+    Board,
+}
+
+impl Display for Difficulty {
+    /// The name of the difficulty in the statistics, which can not contain
+    /// whitespace.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Intermediate => write!(f, "intermediate"),
+            Difficulty::Expert => write!(f, "expert"),
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            } => write!(f, "{}x{}:{}", width, height, mines),
+            Difficulty::Board => write!(f, "board"),
+        }
+    }
+}
+
+/// How many candidate minefields are generated at most for a single game
+/// without guessing, before an ordinary minefield is played instead. Some
+/// sizes and densities can never be solved without guessing.
+const MAX_CANDIDATES: u32 = 10_000;
+
+/// What should be played after the current game is stopped.
+#[derive(Clone, Copy, Debug)]
+enum NextGame {
+    /// A new game with the given difficulty.
+    Difficulty(Difficulty),
+    /// The same game again, generated from the given seed.
+    Restart(Option<u64>),
+    /// A new game with the given generation policy.
+    Policy(GenerationPolicy),
+}
+
+/// Commandline arguments that are accepted
+#[derive(FromArgs)]
+struct CommandLineArguments {
+    /// difficulty of the game, either 'easy', 'intermediate', 'expert' or a custom size as '<width>x<height>:<mines>'. Easy by default.
+    #[argh(option, from_str_fn(difficulty_from_str), short = 'd')]
+    difficulty: Option<Difficulty>,
+
+    /// seed for the first minefield, random by default
+    #[argh(option)]
+    seed: Option<u64>,
+
+    /// how mines are placed in relation to the first reveal, either 'random', 'safe', 'opening' or 'classic'. Random by default.
+    #[argh(option)]
+    policy: Option<GenerationPolicy>,
+
+    /// which tiles are neighbours, either 'moore', 'torus', 'orthogonal', 'knight' or 'hex'. Moore (the 8 surrounding tiles) by default.
+    #[argh(option)]
+    topology: Option<Topology>,
+
+    /// flagging a flagged tile marks it with a question mark before clearing it
+    #[argh(switch)]
+    question_marks: bool,
+
+    /// only play minefields that the AI can solve without guessing, starting from the center of the minefield which is revealed for you
+    #[argh(switch)]
+    no_guess: bool,
+
+    /// path to an MBF board to play, overrides the difficulty
+    #[argh(option, from_str_fn(board_from_path))]
+    board: Option<CustomMinefield>,
+
+    /// directory where the replays of all finished games are saved, not saved by default
+    #[argh(option)]
+    replays: Option<PathBuf>,
+
+    /// file where the statistics of all finished games are kept per difficulty, not kept by default
+    #[argh(option)]
+    stats: Option<PathBuf>,
+
+    /// path to a replay to watch instead of playing
+    #[argh(option, from_str_fn(replay_from_path))]
+    replay: Option<Replay<CUSTOM_MAX_SIZE, CUSTOM_MAX_SIZE>>,
+}
+
+fn main() {
+    let args: CommandLineArguments = argh::from_env();
+    let mut window = GameWindow::start();
+    let mut game = Game::init(&mut window);
+
+    if let Some(replay) = args.replay {
+        match ReplayPlayer::new(replay) {
+            Ok(player) => replay_main(&mut game, player),
+            Err(e) => eprintln!("Could not play the replay: {:?}", e),
+        }
+        return;
+    }
+
+    let mut difficulty = match args.board {
+        Some(_) => Difficulty::Board,
+        None => args.difficulty.unwrap_or(Difficulty::Easy),
+    };
+    let mut seed = args.seed;
+    let mut policy = args.policy.unwrap_or_default();
+    let topology = args.topology.unwrap_or_default();
+    loop {
+        game.extra_layout.clear();
+        game.append_extra(format!("Difficulty: {:?}\n", difficulty), None, None);
+        game.append_extra(format!("Policy: {:?}\n", policy), None, None);
+        game.append_extra(format!("Topology: {:?}\n", topology), None, None);
+        if args.no_guess {
+            game.append_extra("No guessing\n", None, None);
+        }
+        game.append_extra("\n", None, None);
+        game.append_keybind("1", format!("{:?}", Difficulty::Easy));
+        game.append_keybind("2", format!("{:?}", Difficulty::Intermediate));
+        game.append_keybind("3", format!("{:?}", Difficulty::Expert));
+        game.append_keybind("R", "Restart same board");
+        game.append_keybind("P", "Change policy");
+        game.append_keybind("Z/Y", "Undo/Redo");
+        game.append_keybind("B", "Print board");
+        match start_game(
+            &mut game,
+            difficulty,
+            seed,
+            policy,
+            (topology, args.question_marks, args.no_guess),
+            args.board.as_ref(),
+            (args.replays.as_deref(), args.stats.as_deref()),
+        ) {
+            Some(NextGame::Difficulty(next)) => {
+                difficulty = next;
+                seed = None;
+            }
+            Some(NextGame::Restart(same)) => seed = same,
+            Some(NextGame::Policy(next)) => {
+                policy = next;
+                seed = None;
+            }
+            None => break,
+        }
+    }
+}
+
+fn start_game(
+    game: &mut Game,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    policy: GenerationPolicy,
+    (topology, question_marks, no_guess): (Topology, bool, bool),
+    board: Option<&CustomMinefield>,
+    paths: (Option<&Path>, Option<&Path>),
+) -> Option<NextGame> {
+    game.timer = 0.;
+    let seed = seed.unwrap_or_else(rand::random);
+    let settings = (seed, policy, topology, question_marks);
+    let game_settings = (difficulty, policy, paths);
+    match difficulty {
+        Difficulty::Easy => play::<10, 10>(game, ((10, 10), 10), settings, no_guess, game_settings),
+        Difficulty::Intermediate => {
+            play::<16, 16>(game, ((16, 16), 40), settings, no_guess, game_settings)
+        }
+        Difficulty::Expert => {
+            play::<30, 16>(game, ((30, 16), 99), settings, no_guess, game_settings)
+        }
+        Difficulty::Custom {
+            width,
+            height,
+            mines,
+        } => play::<CUSTOM_MAX_SIZE, CUSTOM_MAX_SIZE>(
+            game,
+            ((width, height), mines),
+            settings,
+            no_guess,
+            game_settings,
+        ),
+        Difficulty::Board => {
+            let mut board = board.unwrap().clone();
+            board.set_topology(topology);
+            board.set_question_marks(question_marks);
+            game_main(game, board, None, game_settings)
+        }
+    }
+}
+
+/// Generate a minefield and play it. If `no_guess` is set, the minefield can
+/// be solved without guessing from its starting tile, which is then revealed
+/// first. If no such minefield is found, an ordinary one is played instead.
+fn play<const W: usize, const H: usize>(
+    game: &mut Game,
+    (size, mines): ((u16, u16), u16),
+    settings: (u64, GenerationPolicy, Topology, bool),
+    no_guess: bool,
+    game_settings: (Difficulty, GenerationPolicy, (Option<&Path>, Option<&Path>)),
+) -> Option<NextGame> {
+    if !no_guess {
+        return game_main::<W, H>(game, generate(size, mines, settings), None, game_settings);
+    }
+    let Some(found) = generate_without_guessing::<W, H>(size, mines, settings) else {
+        eprintln!(
+            "None of the {} candidate minefields could be solved without guessing, playing an ordinary minefield",
+            MAX_CANDIDATES
+        );
+        game.append_extra("Guessing may be needed\n", None, None);
+        return game_main::<W, H>(game, generate(size, mines, settings), None, game_settings);
+    };
+    game.append_extra(
+        format!("Candidate boards generated: {}\n", found.candidates),
+        None,
+        None,
+    );
+    game_main(game, found.minefield, Some(found.start), game_settings)
+}
+
+fn generate<const W: usize, const H: usize>(
+    (width, height): (u16, u16),
+    mines: u16,
+    (seed, policy, topology, question_marks): (u64, GenerationPolicy, Topology, bool),
+) -> Minefield<W, H> {
+    let mut minefield = Minefield::generate_sized(width, height, mines, seed, policy).unwrap();
+    minefield.set_topology(topology);
+    minefield.set_question_marks(question_marks);
+    minefield
+}
+
+/// Generate candidate minefields from consecutive seeds starting from the
+/// given one, until one of them can be solved without guessing, see
+/// [minesweeper_ai::ai::no_guess]. Returns None if none of the
+/// [MAX_CANDIDATES] candidates could be solved without guessing.
+fn generate_without_guessing<const W: usize, const H: usize>(
+    (width, height): (u16, u16),
+    mines: u16,
+    (seed, policy, topology, question_marks): (u64, GenerationPolicy, Topology, bool),
+) -> Option<NoGuessMinefield<W, H>> {
+    let mut next_seed = seed;
+    let mut candidate = || {
+        let seed = next_seed;
+        next_seed = next_seed.wrapping_add(1);
+        generate(
+            (width, height),
+            mines,
+            (seed, policy, topology, question_marks),
+        )
+    };
+    generate_no_guess(&mut candidate, MAX_CANDIDATES)
+}
+
+fn board_from_path(value: &str) -> Result<CustomMinefield, String> {
+    Minefield::load_mbf(value).map_err(|e| match e {
+        MinefieldError::Io(kind) => {
+            format!("could not read the board: {}", std::io::Error::from(kind))
+        }
+        MinefieldError::InvalidSize => {
+            format!("the board must be at most {0}x{0}", CUSTOM_MAX_SIZE)
+        }
+        _ => String::from("the board is not a valid MBF board"),
+    })
+}
+
+fn difficulty_from_str(value: &str) -> Result<Difficulty, String> {
+    Ok(match value.to_lowercase().trim() {
+        "easy" => Difficulty::Easy,
+        "intermediate" | "med" | "medium" => Difficulty::Intermediate,
+        "expert" | "ex" | "hard" => Difficulty::Expert,
+        custom => {
+            let error = format!(
+                "difficulty must be either 'easy', 'intermediate', 'expert' or '<width>x<height>:<mines>' with a size of at most {0}x{0}",
+                CUSTOM_MAX_SIZE
+            );
+            let (size, mines) = custom.split_once(':').ok_or(&error)?;
+            let (width, height) = size.split_once('x').ok_or(&error)?;
+            let (width, height, mines) = match (width.parse(), height.parse(), mines.parse()) {
+                (Ok(width), Ok(height), Ok(mines)) => (width, height, mines),
+                _ => Err(&error)?,
+            };
+            if width == 0
+                || height == 0
+                || width as usize > CUSTOM_MAX_SIZE
+                || height as usize > CUSTOM_MAX_SIZE
+            {
+                Err(&error)?
+            } else if mines as usize >= width as usize * height as usize {
+                Err("there must be fewer mines than there are tiles")?
+            }
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            }
+        }
+    })
+}
+
+fn replay_from_path(value: &str) -> Result<Replay<CUSTOM_MAX_SIZE, CUSTOM_MAX_SIZE>, String> {
+    Replay::load(value).map_err(|e| match e {
+        MinefieldError::Io(kind) => {
+            format!("could not read the replay: {}", std::io::Error::from(kind))
+        }
+        MinefieldError::InvalidFormat { line } => {
+            format!("the replay is not valid on line {}", line)
+        }
+        _ => String::from("the replay is not valid"),
+    })
+}
+
+fn next_policy(policy: GenerationPolicy) -> GenerationPolicy {
+    match policy {
+        GenerationPolicy::Random => GenerationPolicy::SafeFirstClick,
+        GenerationPolicy::SafeFirstClick => GenerationPolicy::OpeningFirstClick,
+        GenerationPolicy::OpeningFirstClick => GenerationPolicy::ClassicRelocation,
+        GenerationPolicy::ClassicRelocation => GenerationPolicy::Random,
+    }
+}
+
+/// Play the given minefield until the game is stopped. If a starting tile is
+/// given, it is revealed before the player gets to play.
+fn game_main<const W: usize, const H: usize>(
+    game: &mut Game,
+    mut minefield: Minefield<W, H>,
+    start: Option<Coord<W, H>>,
+    (difficulty, policy, (replays, stats)): (
+        Difficulty,
+        GenerationPolicy,
+        (Option<&Path>, Option<&Path>),
+    ),
+) -> Option<NextGame> {
+    let mut mouse_pressed = false;
+    let mut clicks = ClickStats::default();
+    let mut finished = false;
+    let mut undone = false;
+    let (mut left_held, mut right_held) = (false, false);
+    let mut chording = false;
+    let mut replay = Replay::new(&minefield);
+    // The starting tile is revealed before the history begins, so that it can
+    // not be undone
+    if let Some(start) = start {
+        if minefield.reveal(start).is_ok() {
+            replay.record(Action::Reveal(start));
+        }
+    }
+    let subscription = minefield.subscribe();
+    let mut history = History::new(minefield);
+    let mut next_game = None;
+
+    while let (Some(events), None) = (game.update(), next_game) {
+        for event in events.events {
+            let next = match event {
+                Event::MouseButtonUp {
+                    mouse_btn, x, y, ..
+                } => {
+                    let both_held = left_held && right_held;
+                    match mouse_btn {
+                        MouseButton::Left => left_held = false,
+                        MouseButton::Right => right_held = false,
+                        _ => {}
+                    }
+                    mouse_pressed = false;
+                    if mouse_btn == MouseButton::Left && game.is_reset_button((x, y)) {
+                        next_game = Some(NextGame::Difficulty(difficulty));
+                        continue;
+                    }
+                    // Releasing the other button after a left+right chord
+                    // should not reveal or flag anything.
+                    let after_chord = std::mem::replace(&mut chording, both_held);
+                    let chord = both_held || mouse_btn == MouseButton::Middle;
+                    let action = match (mouse_btn, game.get_coord(history.minefield(), (x, y))) {
+                        (_, Some(coord)) if chord => Some(Action::Chord(coord)),
+                        _ if after_chord => None,
+                        (MouseButton::Left, Some(coord)) => Some(Action::Reveal(coord)),
+                        (MouseButton::Right, Some(coord)) => Some(Action::Flag(coord)),
+                        _ => None,
+                    };
+                    if let (Some(action), GameState::Pending) =
+                        (action, history.minefield().game_state())
+                    {
+                        history.apply(action).ok();
+                        let events = history.poll(&subscription);
+                        if !events.is_empty() {
+                            replay.record(action);
+                        }
+                        clicks.record(action, events);
+                        if history.minefield().game_state() != GameState::Pending && !finished {
+                            finished = true;
+                            let stats = stats.map(|path| (path, undone));
+                            show_results(game, history.minefield(), clicks, difficulty, stats);
+                        }
+                    }
+                    None
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => match keycode {
+                    Keycode::Num1 => Some(NextGame::Difficulty(Difficulty::Easy)),
+                    Keycode::Num2 => Some(NextGame::Difficulty(Difficulty::Intermediate)),
+                    Keycode::Num3 => Some(NextGame::Difficulty(Difficulty::Expert)),
+                    Keycode::R => Some(NextGame::Restart(history.minefield().seed())),
+                    Keycode::P => Some(NextGame::Policy(next_policy(policy))),
+                    Keycode::Z => {
+                        if history.undo().is_some() {
+                            // Restoring the tiles is not a click
+                            history.poll(&subscription);
+                            replay.forget_latest();
+                            undone = true;
+                            // A game that is finished again after undoing its
+                            // end is recorded again, as a game with undos
+                            finished = history.minefield().game_state() != GameState::Pending;
+                        }
+                        None
+                    }
+                    Keycode::Y => {
+                        if let Some(step) = history.redo() {
+                            replay.record(step.action);
+                            history.poll(&subscription);
+                        }
+                        None
+                    }
+                    Keycode::B => {
+                        match history.minefield().to_text() {
+                            Ok(text) => print!("{}", text),
+                            Err(_) => eprintln!(
+                                "The mines are only placed on the first reveal, reveal a tile first"
+                            ),
+                        }
+                        None
+                    }
+                    _ => None,
+                },
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    match mouse_btn {
+                        MouseButton::Left => left_held = true,
+                        MouseButton::Right => right_held = true,
+                        _ => {}
+                    }
+                    mouse_pressed = true;
+                    None
+                }
+                _ => None,
+            };
+            next_game = next.or(next_game);
+        }
+        let minefield = history.minefield();
+        let hover_tile = if mouse_pressed && minefield.game_state() == GameState::Pending {
+            game.get_coord(minefield, events.mouse_pos)
+        } else {
+            None
+        };
+        game.timer_paused = minefield.game_state() != GameState::Pending;
+        game.draw(minefield, hover_tile);
+    }
+
+    let game_state = history.minefield().game_state();
+    if let (Some(dir), GameState::Victory | GameState::GameOver) = (replays, game_state) {
+        let path = dir.join(replay.file_name(game_state));
+        if let Err(e) = replay.save(&path) {
+            eprintln!("Could not save the replay to {}: {:?}", path.display(), e);
+        }
+    }
+    next_game
+}
+
+/// Show the clicks of a finished game on the side panel, and record the game
+/// into the statistics if they are kept. Games where something was undone are
+/// recorded separately from the rest, under the name of the difficulty
+/// followed by `-undo`.
+fn show_results<const W: usize, const H: usize>(
+    game: &mut Game,
+    minefield: &Minefield<W, H>,
+    clicks: ClickStats,
+    difficulty: Difficulty,
+    stats: Option<(&Path, bool)>,
+) {
+    let time = Duration::from_secs_f32(game.timer);
+    game.append_extra(
+        format!(
+            "\nClicks: {} ({} left, {} right, {} chords)\n",
+            clicks.total(),
+            clicks.left,
+            clicks.right,
+            clicks.chords
+        ),
+        Some(20.),
+        None,
+    );
+    game.append_extra(
+        format!("Wasted clicks: {}\n", clicks.wasted),
+        Some(20.),
+        None,
+    );
+    game.append_extra(
+        format!("Clicks/s: {:.2}\n", clicks.per_second(time)),
+        Some(20.),
+        None,
+    );
+    let bbbv = minefield.bbbv().unwrap_or_default();
+    if minefield.game_state() == GameState::Victory {
+        game.append_extra(
+            format!("Efficiency: {:.0}%\n", clicks.efficiency(bbbv) * 100.),
+            Some(20.),
+            None,
+        );
+    }
+
+    let Some((path, undone)) = stats else {
+        return;
+    };
+    let mut statistics = match Statistics::load(path) {
+        Ok(statistics) => statistics,
+        Err(MinefieldError::Io(std::io::ErrorKind::NotFound)) => Statistics::default(),
+        Err(e) => {
+            eprintln!(
+                "Could not load the statistics from {}: {:?}",
+                path.display(),
+                e
+            );
+            return;
+        }
+    };
+    let name = if undone {
+        format!("{}-undo", difficulty)
+    } else {
+        difficulty.to_string()
+    };
+    let results = statistics.record(&name, minefield.game_state(), time, bbbv, clicks);
+    if let Err(e) = statistics.save(path) {
+        eprintln!(
+            "Could not save the statistics to {}: {:?}",
+            path.display(),
+            e
+        );
+    }
+    game.append_extra(
+        format!(
+            "\nWon {} / {} on {}\n",
+            results.victories, results.games, name
+        ),
+        Some(20.),
+        None,
+    );
+    if let Some(best) = results.best_time {
+        game.append_extra(
+            format!("Best time: {:.1}\n", best.as_secs_f32()),
+            Some(20.),
+            None,
+        );
+        game.append_extra(
+            format!(
+                "Average 3BV/s: {:.2}, efficiency: {:.0}%\n",
+                results.bbbv_per_second(),
+                results.efficiency() * 100.
+            ),
+            Some(20.),
+            None,
+        );
+    }
+}
+
+/// How far the replay is moved when seeking
+const SEEK: Duration = Duration::from_secs(5);
+
+fn replay_main<const W: usize, const H: usize>(game: &mut Game, mut player: ReplayPlayer<W, H>) {
+    game.timer_paused = true;
+    while let Some(events) = game.update() {
+        for event in events.events {
+            if let Event::KeyDown {
+                keycode: Some(keycode),
+                ..
+            } = event
+            {
+                match keycode {
+                    Keycode::Space => player.paused = !player.paused,
+                    Keycode::Up => player.speed = (player.speed * 2.).min(64.),
+                    Keycode::Down => player.speed = (player.speed / 2.).max(1. / 8.),
+                    Keycode::Right => {
+                        player.paused = true;
+                        player.step_forward();
+                    }
+                    Keycode::Left => {
+                        player.paused = true;
+                        player.step_backward();
+                    }
+                    Keycode::PageUp => player.seek(player.time() + SEEK),
+                    Keycode::PageDown => player.seek(player.time().saturating_sub(SEEK)),
+                    Keycode::Home => player.seek(Duration::ZERO),
+                    _ => {}
+                }
+            }
+        }
+        player.advance(Duration::from_secs_f32(events.delta));
+
+        game.extra_layout.clear();
+        game.append_extra(format!("Speed: x{}\n", player.speed), None, None);
+        game.append_extra(
+            format!(
+                "Action {} / {}{}\n\n",
+                player.position(),
+                player.replay().actions.len(),
+                if player.paused { " (paused)" } else { "" }
+            ),
+            None,
+            None,
+        );
+        game.append_keybind("Space", "Pause");
+        game.append_keybind("Up/Down", "Speed");
+        game.append_keybind("Left/Right", "Step");
+        game.append_keybind("PgUp/PgDn", format!("Seek {:?}", SEEK));
+        game.append_keybind("Home", "Restart");
+
+        game.timer = player.time().as_secs_f32();
+        let marker = player.latest().map(|action| action.coord());
+        game.draw_marked(player.minefield(), None, marker);
+    }
+}
@@ -0,0 +1,213 @@
+//! Contains the standard metrics of how difficult a [Minefield] is to clear,
+//! which only depend on where the mines are:
+//! - An opening is a connected area of tiles without neighbouring mines,
+//!   which is revealed with a single click along with the labels around it.
+//! - An isolated label is a label that does not border any opening, so it has
+//!   to be revealed with a click of its own.
+//! - An island is a connected group of isolated labels.
+//! - 3BV (Bechtel's Board Benchmark Value) is the least amount of clicks
+//!   needed to clear the minefield without flags or chords, which is the
+//!   amount of openings and isolated labels together.
+//! - ZiNi is the least amount of clicks needed when flags and chords are
+//!   used. It is estimated greedily: every opening is clicked, after which the
+//!   label that saves the most clicks by flagging its mines and chording it
+//!   is chorded, until chording no longer saves any clicks.
+//!
+//! For example a 7x1 minefield with two mines has a single opening in the
+//! middle, and two isolated labels at the ends:
+//! ```
+//! # use miinaharava::minefield::*;
+//! let minefield: Minefield<7, 1> = "7x1:2\n.*...*.\n".parse().unwrap();
+//! let metrics = minefield.metrics().unwrap();
+//!
+//! assert_eq!((metrics.openings, metrics.isolated, metrics.islands), (1, 2, 2));
+//! assert_eq!(metrics.bbbv, 3);
+//! ```
+
+use crate::minefield::{Cell, Coord, Matrix, Minefield, MinefieldError};
+
+/// The difficulty metrics of a minefield, see [crate::analysis].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Metrics {
+    /// 3BV, the least amount of clicks needed without flags or chords.
+    pub bbbv: u32,
+    /// The amount of openings.
+    pub openings: u32,
+    /// The amount of labels that do not border any opening.
+    pub isolated: u32,
+    /// The amount of connected groups of isolated labels.
+    pub islands: u32,
+    /// ZiNi, the least amount of clicks needed with flags and chords.
+    pub zini: u32,
+}
+
+impl<const W: usize, const H: usize> Minefield<W, H> {
+    /// Calculate the difficulty metrics of the minefield, see
+    /// [crate::analysis].
+    ///
+    /// # Errors
+    /// - [MinefieldError::MinesNotPlaced] if the mines are only placed on the
+    ///   first reveal, which has not happened yet.
+    pub fn metrics(&self) -> Result<Metrics, MinefieldError> {
+        let (openings, count) = self.openings()?;
+        let mut isolated = Matrix([[false; W]; H]);
+        for coord in self.coords() {
+            let borders_opening = self.neighbours(coord).iter().any(|c| openings.get(*c) > 0);
+            if let (Cell::Label(_), false) = (self.cell_contents(coord), borders_opening) {
+                isolated.set(coord, true);
+            }
+        }
+
+        let mut islands = 0;
+        let mut visited = Matrix([[false; W]; H]);
+        for coord in self.coords() {
+            if isolated.get(coord) && !visited.get(coord) {
+                islands += 1;
+                self.fill(coord, &mut visited, true, |c| isolated.get(c));
+            }
+        }
+
+        let isolated = isolated.iter().flatten().filter(|i| **i).count() as u32;
+        Ok(Metrics {
+            bbbv: count + isolated,
+            openings: count,
+            isolated,
+            islands,
+            zini: self.zini(&openings, count),
+        })
+    }
+
+    /// Calculate only the 3BV of the minefield, see [crate::analysis]. Faster
+    /// than [Minefield::metrics].
+    ///
+    /// # Errors
+    /// - [MinefieldError::MinesNotPlaced] if the mines are only placed on the
+    ///   first reveal, which has not happened yet.
+    pub fn bbbv(&self) -> Result<u32, MinefieldError> {
+        let (openings, count) = self.openings()?;
+        let isolated = self
+            .coords()
+            .filter(|coord| matches!(self.cell_contents(*coord), Cell::Label(_)))
+            .filter(|coord| {
+                self.neighbours(*coord)
+                    .iter()
+                    .all(|c| openings.get(*c) == 0)
+            })
+            .count() as u32;
+        Ok(count + isolated)
+    }
+
+    /// Find all the openings, returning a matrix where every tile without
+    /// neighbouring mines has the number of its opening starting from 1, and
+    /// every other tile has 0. Also returns the amount of openings.
+    fn openings(&self) -> Result<(Matrix<u32, W, H>, u32), MinefieldError> {
+        if self.coords().filter(|c| self.is_mine(*c)).count() != self.mines as usize {
+            return Err(MinefieldError::MinesNotPlaced);
+        }
+        let mut openings = Matrix([[0; W]; H]);
+        let mut count = 0;
+        for coord in self.coords() {
+            if self.cell_contents(coord) == Cell::Empty && openings.get(coord) == 0 {
+                count += 1;
+                self.fill(coord, &mut openings, count, |c| {
+                    self.cell_contents(c) == Cell::Empty
+                });
+            }
+        }
+        Ok((openings, count))
+    }
+
+    /// Estimate ZiNi greedily, see [crate::analysis].
+    fn zini(&self, openings: &Matrix<u32, W, H>, count: u32) -> u32 {
+        // Click every opening first
+        let mut clicks = count;
+        let mut revealed = Matrix([[false; W]; H]);
+        let mut flagged = Matrix([[false; W]; H]);
+        for coord in self.coords() {
+            if openings.get(coord) > 0 {
+                revealed.set(coord, true);
+                for c in self.neighbours(coord) {
+                    revealed.set(c, true);
+                }
+            }
+        }
+
+        // Then chord the label that saves the most clicks, until none does
+        loop {
+            let mut best: Option<(i32, u32, Coord<W, H>)> = None;
+            for coord in self.coords() {
+                if !matches!(self.cell_contents(coord), Cell::Label(_)) {
+                    continue;
+                }
+                let neighbours = self.neighbours(coord);
+                let closed = neighbours
+                    .iter()
+                    .filter(|c| !self.is_mine(**c) && !revealed.get(**c))
+                    .count() as i32;
+                if closed == 0 {
+                    continue;
+                }
+                let unflagged = neighbours
+                    .iter()
+                    .filter(|c| self.is_mine(**c) && !flagged.get(**c))
+                    .count() as i32;
+                let click = !revealed.get(coord) as i32;
+                // Flagging, clicking and chording the label saves clicking the
+                // label and its hidden neighbours one by one
+                let cost = unflagged + click + 1;
+                let premium = (closed + click) - cost;
+                if best.map(|(best, _, _)| premium > best).unwrap_or(true) {
+                    best = Some((premium, cost as u32, coord));
+                }
+            }
+            let Some((_, cost, coord)) = best.filter(|(premium, _, _)| *premium > 0) else {
+                break;
+            };
+
+            clicks += cost;
+            revealed.set(coord, true);
+            for c in self.neighbours(coord) {
+                if self.is_mine(c) {
+                    flagged.set(c, true);
+                } else {
+                    revealed.set(c, true);
+                }
+            }
+        }
+
+        // Finally click every label that is still hidden
+        clicks
+            + self
+                .coords()
+                .filter(|c| !self.is_mine(*c) && !revealed.get(*c))
+                .count() as u32
+    }
+
+    /// Iterate over every coordinate within the actual size of the minefield.
+    fn coords(&self) -> impl Iterator<Item = Coord<W, H>> {
+        let (width, height) = (self.width(), self.height());
+        (0..height).flat_map(move |y| (0..width).map(move |x| Coord(x, y)))
+    }
+
+    /// Set every tile connected to `start` through tiles for which `include`
+    /// returns true to `mark`, including `start` itself. Tiles that are not
+    /// the default value are already visited, and are not filled through.
+    fn fill<T: Copy + Default + PartialEq>(
+        &self,
+        start: Coord<W, H>,
+        visited: &mut Matrix<T, W, H>,
+        mark: T,
+        include: impl Fn(Coord<W, H>) -> bool,
+    ) {
+        let mut stack = vec![start];
+        visited.set(start, mark);
+        while let Some(coord) = stack.pop() {
+            for c in self.neighbours(coord) {
+                if visited.get(c) == T::default() && include(c) {
+                    visited.set(c, mark);
+                    stack.push(c);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Contains [MinefieldEvent], the typed changes that happen on a [Minefield],
+//! so that renderers, replay recorders and statistics do not need to compare
+//! the [Minefield::field] before and after every action.
+//!
+//! Events are only kept once something has subscribed to them with
+//! [Minefield::subscribe], so minefields that nobody observes (like the ones
+//! the AI plays) do not pay for them. Every subscriber gets its own
+//! [Subscription], and reads the events that happened since its previous read
+//! with [Minefield::poll]. Events are only kept until every subscription has
+//! read them, and a subscription that is no longer read should be given back
+//! with [Minefield::unsubscribe]. Events are not part of the minefield itself,
+//! so they are not cloned along with it, and do not affect comparing
+//! minefields.
+//! ```
+//! # use miinaharava::{events::MinefieldEvent, minefield::*};
+//! let mut minefield: Minefield<3, 1> = "3x1:1\n..*\n".parse().unwrap();
+//! let subscription = minefield.subscribe();
+//!
+//! minefield.flag(Coord(2, 0)).unwrap();
+//! assert_eq!(
+//!     minefield.poll(&subscription),
+//!     &[MinefieldEvent::FlagPlaced(Coord(2, 0))]
+//! );
+//!
+//! minefield.reveal(Coord(0, 0)).unwrap();
+//! assert_eq!(
+//!     minefield.poll(&subscription),
+//!     &[
+//!         MinefieldEvent::Revealed(Coord(0, 0), Cell::Empty),
+//!         MinefieldEvent::Revealed(Coord(1, 0), Cell::Label(1)),
+//!         MinefieldEvent::GameWon,
+//!     ]
+//! );
+//! assert!(minefield.poll(&subscription).is_empty());
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::minefield::{Cell, Coord, Minefield};
+
+/// A single change on a [Minefield], see [crate::events].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinefieldEvent<const W: usize, const H: usize> {
+    /// A tile without a mine was revealed, either [Cell::Empty] or
+    /// [Cell::Label]. Any flag or question mark on it is gone.
+    Revealed(Coord<W, H>, Cell),
+    /// A mine was revealed.
+    MineExploded(Coord<W, H>),
+    /// A hidden tile was flagged.
+    FlagPlaced(Coord<W, H>),
+    /// A flag was removed, either leaving the tile hidden or marking it with a
+    /// question mark.
+    FlagRemoved(Coord<W, H>),
+    /// A flagged tile was marked with a question mark.
+    QuestionPlaced(Coord<W, H>),
+    /// A question mark was removed, either leaving the tile hidden or flagging
+    /// it.
+    QuestionRemoved(Coord<W, H>),
+    /// A tile was set directly to the given cell by undoing or redoing an
+    /// action, see [crate::history].
+    Restored(Coord<W, H>, Cell),
+    /// Every tile without a mine has been revealed.
+    GameWon,
+    /// A mine has been revealed.
+    GameLost,
+}
+
+/// A single subscriber to the events of a [Minefield], see [crate::events].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Subscription {
+    /// The id of the [EventQueue] subscribed to
+    queue: u64,
+    /// The index of the subscription in the queue
+    index: usize,
+}
+
+/// The id of the next [EventQueue], so that subscriptions of one queue are
+/// never mistaken for subscriptions of another.
+static NEXT_QUEUE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The events of a [Minefield] that some subscription has not read yet.
+#[derive(Debug)]
+pub(crate) struct EventQueue<const W: usize, const H: usize> {
+    /// Unique id of the queue
+    id: u64,
+    /// The unread events, oldest first
+    pub(crate) events: Vec<MinefieldEvent<W, H>>,
+    /// How many events have been dropped from the front of `events`
+    dropped: usize,
+    /// How many events every subscription has read so far, counting the
+    /// dropped ones, or None if the subscription is given back
+    read: Vec<Option<usize>>,
+}
+
+impl<const W: usize, const H: usize> EventQueue<W, H> {
+    /// Create an empty queue without subscriptions.
+    fn new() -> Self {
+        EventQueue {
+            id: NEXT_QUEUE_ID.fetch_add(1, Ordering::Relaxed),
+            events: Vec::new(),
+            dropped: 0,
+            read: Vec::new(),
+        }
+    }
+
+    /// Return how many events the given subscription has read, or None if it
+    /// is not a subscription of this queue or it is given back.
+    fn read_mut(&mut self, subscription: &Subscription) -> Option<&mut Option<usize>> {
+        if subscription.queue != self.id {
+            return None;
+        }
+        self.read.get_mut(subscription.index)
+    }
+
+    /// Drop the events that every subscription has already read.
+    fn drop_read(&mut self) {
+        let end = self.dropped + self.events.len();
+        let read = self.read.iter().flatten().fold(end, |a, b| a.min(*b));
+        self.events.drain(..read - self.dropped);
+        self.dropped = read;
+    }
+}
+
+impl<const W: usize, const H: usize> Minefield<W, H> {
+    /// Start keeping the events of the minefield, returning a subscription
+    /// that receives every event from now on, see [crate::events].
+    pub fn subscribe(&mut self) -> Subscription {
+        let queue = self.events.get_or_insert_with(EventQueue::new);
+        queue.read.push(Some(queue.dropped + queue.events.len()));
+        Subscription {
+            queue: queue.id,
+            index: queue.read.len() - 1,
+        }
+    }
+
+    /// Stop keeping the events for the given subscription. Once every
+    /// subscription is given back, events are no longer kept at all.
+    /// Subscriptions of other minefields are ignored.
+    pub fn unsubscribe(&mut self, subscription: Subscription) {
+        let Some(queue) = &mut self.events else {
+            return;
+        };
+        if let Some(read) = queue.read_mut(&subscription) {
+            *read = None;
+        }
+        if queue.read.iter().all(Option::is_none) {
+            self.events = None;
+        } else {
+            queue.drop_read();
+        }
+    }
+
+    /// Return every event that has happened since the previous poll of the
+    /// subscription, or since subscribing, see [crate::events]. The events
+    /// returned by the previous poll are dropped, unless another subscription
+    /// has not read them yet. Subscriptions of other minefields receive
+    /// nothing.
+    pub fn poll(&mut self, subscription: &Subscription) -> &[MinefieldEvent<W, H>] {
+        let Some(queue) = &mut self.events else {
+            return &[];
+        };
+        queue.drop_read();
+        let end = queue.dropped + queue.events.len();
+        let Some(Some(read)) = queue.read_mut(subscription) else {
+            return &[];
+        };
+        let previous = std::mem::replace(read, end);
+        &queue.events[previous - queue.dropped..]
+    }
+
+    /// Keep the event if anything has subscribed to the events.
+    #[inline]
+    pub(crate) fn emit(&mut self, event: MinefieldEvent<W, H>) {
+        if let Some(queue) = &mut self.events {
+            queue.events.push(event);
+        }
+    }
+}
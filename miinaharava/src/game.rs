@@ -7,7 +7,7 @@
 use std::time::Instant;
 
 use fontdue::{
-    layout::{CoordinateSystem, HorizontalAlign, Layout, LayoutSettings, TextStyle},
+    layout::{CoordinateSystem, HorizontalAlign, Layout, LayoutSettings, TextStyle, VerticalAlign},
     Font,
 };
 use fontdue_sdl2::FontTexture;
@@ -22,12 +22,15 @@ use sdl2::{
 };
 
 use crate::{
-    minefield::{Coord, GameState, Minefield},
+    minefield::{Coord, GameState, Matrix, Minefield},
     minefield_renderer::MinefieldRenderer,
 };
 
 static FONT: &[u8] = include_bytes!("./resources/Outfit-Medium.ttf");
 
+/// Height of the status bar above the minefield in pixels
+const STATUS_HEIGHT: u32 = 60;
+
 /// Represents the Window for the Game, which is then used by Game to render
 /// stuff on.
 pub struct GameWindow {
@@ -70,6 +73,8 @@ pub struct Game<'a> {
     minefield_renderer: MinefieldRenderer<'a>,
     fonts: [Font; 1],
     layout: Layout<Color>,
+    status_layout: Layout<Color>,
+    reset_button: Rect,
     canvas: &'a mut Canvas<Window>,
     font_texture: FontTexture<'a>,
     event_pump: &'a mut EventPump,
@@ -105,7 +110,7 @@ pub struct GameEvents {
 impl<'a> Game<'a> {
     /// Initializes renderer with [GameWindow]
     pub fn init(window: &'a mut GameWindow) -> Game<'a> {
-        let minefield_area = Rect::new(0, 0, 900, 720);
+        let minefield_area = Rect::new(0, STATUS_HEIGHT as i32, 900, 720 - STATUS_HEIGHT);
         let minefield_renderer = MinefieldRenderer::init(&window.texture_creator, minefield_area);
 
         let roboto_regular = Font::from_bytes(FONT, Default::default()).unwrap();
@@ -127,9 +132,13 @@ impl<'a> Game<'a> {
             ..Default::default()
         });
 
+        let reset_button = Rect::from_center((450, STATUS_HEIGHT as i32 / 2), 44, 44);
+
         Game {
             minefield_renderer,
             layout,
+            status_layout: Layout::new(CoordinateSystem::PositiveYDown),
+            reset_button,
             canvas: &mut window.canvas,
             fonts: [roboto_regular],
             font_texture,
@@ -184,25 +193,75 @@ impl<'a> Game<'a> {
         &mut self,
         minefield: &Minefield<W, H>,
         hover_tile: Option<Coord<W, H>>,
+    ) {
+        self.draw_marked(minefield, hover_tile, None);
+    }
+
+    /// Same as [Game::draw], but also draws a marker around the given tile, if
+    /// any. Used for showing the tile that was acted on in replays.
+    pub fn draw_marked<const W: usize, const H: usize>(
+        &mut self,
+        minefield: &Minefield<W, H>,
+        hover_tile: Option<Coord<W, H>>,
+        marker: Option<Coord<W, H>>,
+    ) {
+        self.draw_overlaid(minefield, hover_tile, marker, None);
+    }
+
+    /// Same as [Game::draw_marked], but also blends the given colour over
+    /// every tile that has one, for example to show how likely every tile is
+    /// to be a mine. Colours should be partially transparent, so that the
+    /// tiles can still be seen under them.
+    pub fn draw_overlaid<const W: usize, const H: usize>(
+        &mut self,
+        minefield: &Minefield<W, H>,
+        hover_tile: Option<Coord<W, H>>,
+        marker: Option<Coord<W, H>>,
+        overlay: Option<&Matrix<Option<Color>, W, H>>,
     ) {
         self.canvas.set_draw_color(Color::RGB(40, 40, 40));
         self.canvas.clear();
         self.canvas.set_draw_color(Color::RGB(64, 64, 150));
         self.canvas
-            .fill_rect(self.minefield_renderer.get_target::<W, H>())
+            .fill_rect(self.minefield_renderer.get_target(minefield))
             .unwrap();
         self.minefield_renderer
             .draw(minefield, self.canvas, hover_tile);
+        if let Some(overlay) = overlay {
+            self.minefield_renderer
+                .draw_overlay(minefield, self.canvas, overlay);
+        }
+        if let Some(marker) = marker {
+            self.minefield_renderer
+                .draw_marker(minefield, self.canvas, marker);
+        }
+        self.draw_status(minefield);
 
         self.layout.clear();
-        self.append_text(format!("{}, {}\n", W, H), None, None);
+        self.append_text(
+            format!("{}, {}\n", minefield.width(), minefield.height()),
+            None,
+            None,
+        );
         self.append_text(format!("{} mines\n", minefield.mines), None, None);
         self.append_text(format!("{:.1}\n", self.timer), None, None);
+        if let Some(seed) = minefield.seed() {
+            self.append_text(format!("Seed: {}\n", seed), Some(20.), None);
+        }
         match minefield.game_state() {
             GameState::GameOver => {
                 self.append_text("Game over!", None, Some(Color::RGB(0xFF, 0, 0)));
             }
-            GameState::Victory => self.append_text("Victory!", None, Some(Color::RGB(0, 0xFF, 0))),
+            GameState::Victory => {
+                self.append_text("Victory!\n", None, Some(Color::RGB(0, 0xFF, 0)));
+                if let Ok(bbbv) = minefield.bbbv() {
+                    self.append_text(format!("3BV: {}\n", bbbv), Some(20.), None);
+                    if self.timer > 0. {
+                        let per_second = bbbv as f32 / self.timer;
+                        self.append_text(format!("3BV/s: {:.2}\n", per_second), Some(20.), None);
+                    }
+                }
+            }
             _ => {}
         }
 
@@ -216,12 +275,67 @@ impl<'a> Game<'a> {
         self.canvas.present();
     }
 
-    /// Attempt to convert screen-pixel-coordinates into game-tile-coordinates.
+    /// Draws the status bar above the minefield: the amount of mines minus
+    /// the amount of flags on the left, the timer on the right and the reset
+    /// button between them, which shows whether the game was won or lost.
+    fn draw_status<const W: usize, const H: usize>(&mut self, minefield: &Minefield<W, H>) {
+        let (face, color) = match minefield.game_state() {
+            GameState::Pending => (":)", Color::RGB(0xC0, 0xC0, 0xC0)),
+            GameState::Victory => ("B)", Color::RGB(0x40, 0xC0, 0x40)),
+            GameState::GameOver => (":(", Color::RGB(0xD0, 0x40, 0x40)),
+        };
+        self.canvas.set_draw_color(color);
+        self.canvas.fill_rect(self.reset_button).unwrap();
+        self.canvas.set_draw_color(Color::RGB(0x80, 0x80, 0x80));
+        self.canvas.draw_rect(self.reset_button).unwrap();
+
+        let counter = format!("{:03}", minefield.remaining_mines().clamp(-99, 999));
+        let timer = format!("{:03}", (self.timer as u32).min(999));
+        let (button_x, button_y, button_width, button_height) = self.reset_button.into();
+        for (text, x, width, align, color) in [
+            (counter, 20., 200., HorizontalAlign::Left, Color::RED),
+            (timer, 680., 200., HorizontalAlign::Right, Color::RED),
+            (
+                face.to_string(),
+                button_x as f32,
+                button_width as f32,
+                HorizontalAlign::Center,
+                Color::BLACK,
+            ),
+        ] {
+            self.status_layout.reset(&LayoutSettings {
+                x,
+                y: button_y as f32,
+                max_width: Some(width),
+                max_height: Some(button_height as f32),
+                horizontal_align: align,
+                vertical_align: VerticalAlign::Middle,
+                ..Default::default()
+            });
+            self.status_layout.append(
+                &self.fonts,
+                &TextStyle::with_user_data(&text, 36., 0, color),
+            );
+            let _ =
+                self.font_texture
+                    .draw_text(self.canvas, &self.fonts, self.status_layout.glyphs());
+        }
+    }
+
+    /// Whether the given screen-pixel-coordinates are on the reset button of
+    /// the status bar, see [Game::draw].
+    pub fn is_reset_button(&self, mouse: (i32, i32)) -> bool {
+        self.reset_button.contains_point(mouse)
+    }
+
+    /// Attempt to convert screen-pixel-coordinates into game-tile-coordinates
+    /// of the given minefield.
     pub fn get_coord<const W: usize, const H: usize>(
         &self,
+        minefield: &Minefield<W, H>,
         mouse: (i32, i32),
     ) -> Option<Coord<W, H>> {
-        self.minefield_renderer.get_coord(mouse)
+        self.minefield_renderer.get_coord(minefield, mouse)
     }
 
     /// Forcibly exists the game, update will stop returning things.
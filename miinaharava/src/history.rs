@@ -0,0 +1,170 @@
+//! Contains [History], which records every action done on a [Minefield] so
+//! that the game can be stepped backwards and forwards.
+
+use crate::{
+    events::{MinefieldEvent, Subscription},
+    minefield::{Coord, GameState, Minefield, MinefieldError, Reveal},
+};
+
+/// Represents a single action that can be done on a minefield.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action<const W: usize, const H: usize> {
+    /// Reveal the tile, see [Minefield::reveal]
+    Reveal(Coord<W, H>),
+    /// Flag or unflag the tile, see [Minefield::flag]
+    Flag(Coord<W, H>),
+    /// Chord the tile, see [Minefield::chord]
+    Chord(Coord<W, H>),
+}
+
+impl<const W: usize, const H: usize> Action<W, H> {
+    /// Return the coordinate the action is done on.
+    pub fn coord(&self) -> Coord<W, H> {
+        match self {
+            Action::Reveal(coord) | Action::Flag(coord) | Action::Chord(coord) => *coord,
+        }
+    }
+}
+
+/// Represents an action that was done on the minefield, along with everything
+/// that is needed to undo or redo it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step<const W: usize, const H: usize> {
+    /// The action that was done.
+    pub action: Action<W, H>,
+    /// The reveals that the minefield returned for the action.
+    pub reveals: Vec<Reveal<W, H>>,
+    /// Every changed tile as it was before the action.
+    before: Vec<Reveal<W, H>>,
+    /// Every changed tile as it was after the action.
+    after: Vec<Reveal<W, H>>,
+    /// The state of the game before and after the action.
+    game_state: (GameState, GameState),
+}
+
+/// Records every action done on the [Minefield] it holds, so they can be
+/// undone and redone. Undoing a step restores the tiles and the state of the
+/// game as they were, so even the losing reveal can be undone.
+///
+/// Mines that were placed on the first reveal (see
+/// [crate::minefield::GenerationPolicy]) stay where they are even if the first
+/// reveal is undone.
+#[derive(Clone, Debug)]
+pub struct History<const W: usize, const H: usize> {
+    minefield: Minefield<W, H>,
+    steps: Vec<Step<W, H>>,
+    undone: Vec<Step<W, H>>,
+}
+
+impl<const W: usize, const H: usize> History<W, H> {
+    /// Start recording the history of the given minefield.
+    pub fn new(minefield: Minefield<W, H>) -> Self {
+        History {
+            minefield,
+            steps: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// Return the minefield in its current state.
+    #[inline]
+    pub fn minefield(&self) -> &Minefield<W, H> {
+        &self.minefield
+    }
+
+    /// Return the events of the minefield since the previous poll of the
+    /// subscription, see [Minefield::poll].
+    #[inline]
+    pub fn poll(&mut self, subscription: &Subscription) -> &[MinefieldEvent<W, H>] {
+        self.minefield.poll(subscription)
+    }
+
+    /// Return all of the steps done so far, excluding the undone steps.
+    #[inline]
+    pub fn steps(&self) -> &[Step<W, H>] {
+        &self.steps
+    }
+
+    /// Attempts to reveal a tile and records it, see [Minefield::reveal].
+    ///
+    /// # Errors
+    /// Same as [Minefield::reveal], nothing is recorded on errors.
+    pub fn reveal(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        self.apply(Action::Reveal(coord))
+    }
+
+    /// Attempts to flag a tile and records it, see [Minefield::flag].
+    ///
+    /// # Errors
+    /// Same as [Minefield::flag], nothing is recorded on errors.
+    pub fn flag(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        self.apply(Action::Flag(coord))
+    }
+
+    /// Attempts to chord a tile and records it, see [Minefield::chord].
+    ///
+    /// # Errors
+    /// Same as [Minefield::chord], nothing is recorded on errors.
+    pub fn chord(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        self.apply(Action::Chord(coord))
+    }
+
+    /// Attempts to do the given action and records it. Actions that do not
+    /// change anything are not recorded. Doing a new action forgets all the
+    /// undone steps, so they can no longer be redone.
+    ///
+    /// # Errors
+    /// Same as the minefield function of the action, nothing is recorded on
+    /// errors.
+    pub fn apply(&mut self, action: Action<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        let previous_state = self.minefield.game_state();
+        let (reveals, before) = match action {
+            Action::Flag(coord) => {
+                let before = self.minefield.field.get(coord);
+                (self.minefield.flag(coord)?, vec![(coord, before)])
+            }
+            Action::Reveal(coord) | Action::Chord(coord) => {
+                let field = self.minefield.field;
+                let reveals = match action {
+                    Action::Chord(_) => self.minefield.chord(coord)?,
+                    _ => self.minefield.reveal(coord)?,
+                };
+                let before = reveals.iter().map(|(c, _)| (*c, field.get(*c))).collect();
+                (reveals, before)
+            }
+        };
+        let after = before
+            .iter()
+            .map(|(c, _)| (*c, self.minefield.field.get(*c)))
+            .collect::<Vec<_>>();
+
+        if after != before || self.minefield.game_state() != previous_state {
+            self.undone.clear();
+            self.steps.push(Step {
+                action,
+                reveals: reveals.clone(),
+                before,
+                after,
+                game_state: (previous_state, self.minefield.game_state()),
+            });
+        }
+        Ok(reveals)
+    }
+
+    /// Undo the latest step, returning it. None if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<&Step<W, H>> {
+        let step = self.steps.pop()?;
+        self.minefield.restore(&step.before, step.game_state.0);
+        self.undone.push(step);
+        self.undone.last()
+    }
+
+    /// Redo the latest undone step, returning it. None if there is nothing to
+    /// redo.
+    pub fn redo(&mut self) -> Option<&Step<W, H>> {
+        let step = self.undone.pop()?;
+        self.minefield.restore(&step.after, step.game_state.1);
+        self.steps.push(step);
+        self.steps.last()
+    }
+}
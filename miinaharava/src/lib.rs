@@ -5,6 +5,24 @@
 //!
 //! [game] contains everything related to drawing, rendering and capturing
 //! events from the window in question.
+//!
+//! [events] contains the typed changes on a minefield, which can be
+//! subscribed to.
+//!
+//! [history] contains a recorded history of actions on a minefield, which can
+//! be undone and redone.
+//!
+//! [text_format] contains a human-readable text format for minefields and
+//! visible fields.
+//!
+//! [replay] contains recordings of games that can be saved and loaded.
+//!
+//! [mbf] contains import and export of minefields in the binary MBF format.
+//!
+//! [analysis] contains difficulty metrics of minefields, such as 3BV.
+//!
+//! [statistics] contains click statistics of games and persistent statistics
+//! per difficulty.
 
 #![deny(clippy::all)]
 #![warn(missing_docs)]
@@ -12,9 +30,16 @@
 
 pub use sdl2;
 
+pub mod analysis;
+pub mod events;
 pub mod game;
+pub mod history;
+pub mod mbf;
 pub mod minefield;
 pub(crate) mod minefield_renderer;
+pub mod replay;
+pub mod statistics;
+pub mod text_format;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,107 @@
+//! Contains import and export of the binary Minesweeper Board Format (MBF),
+//! which is commonly used for sharing minefields.
+//!
+//! An MBF board consists of
+//! - the width of the minefield as a single byte
+//! - the height of the minefield as a single byte
+//! - the amount of mines as two bytes, most significant byte first
+//! - two bytes for every mine, first the x-coordinate and then the
+//!   y-coordinate
+//!
+//! Only the placement of the mines is stored, so a loaded minefield has
+//! nothing revealed and the first reveal may be a mine, just like with
+//! [crate::minefield::GenerationPolicy::Random].
+
+use std::{fs, path::Path};
+
+use crate::minefield::{Cell, Coord, Matrix, Minefield, MinefieldError};
+
+impl<const W: usize, const H: usize> Minefield<W, H> {
+    /// Load a minefield from the bytes of an MBF board, see [crate::mbf].
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if the size of the board is zero or
+    ///   larger than the minefield can hold.
+    /// - [MinefieldError::InvalidMbf] if the board is cut short, has extra
+    ///   bytes at the end, or has a mine outside of the board or the same mine
+    ///   twice.
+    pub fn from_mbf(bytes: &[u8]) -> Result<Self, MinefieldError> {
+        if bytes.len() < 4 {
+            return Err(MinefieldError::InvalidMbf);
+        }
+        let (width, height) = (bytes[0] as u16, bytes[1] as u16);
+        let count = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let mine_bytes = &bytes[4..];
+        if width == 0 || height == 0 || width as usize > W || height as usize > H {
+            return Err(MinefieldError::InvalidSize);
+        }
+        if mine_bytes.len() != count as usize * 2 {
+            return Err(MinefieldError::InvalidMbf);
+        }
+
+        let mut mines = Matrix([[false; W]; H]);
+        for pair in mine_bytes.chunks_exact(2) {
+            let coord = Coord(pair[0] as u16, pair[1] as u16);
+            if coord.0 >= width || coord.1 >= height || mines.get(coord) {
+                return Err(MinefieldError::InvalidMbf);
+            }
+            mines.set(coord, true);
+        }
+
+        let mut field = Matrix([[Cell::Empty; W]; H]);
+        for y in 0..height {
+            for x in 0..width {
+                field.set(Coord(x, y), Cell::Hidden);
+            }
+        }
+        Ok(Minefield::from_parts(width, height, mines, field))
+    }
+
+    /// Save the mines of the minefield as the bytes of an MBF board, see
+    /// [crate::mbf]. The visible field is not saved.
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if the minefield is wider or higher
+    ///   than 255 tiles, which MBF is unable to store.
+    /// - [MinefieldError::MinesNotPlaced] if the mines are only placed on the
+    ///   first reveal, which has not happened yet.
+    pub fn to_mbf(&self) -> Result<Vec<u8>, MinefieldError> {
+        let (width, height) = match (u8::try_from(self.width()), u8::try_from(self.height())) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(MinefieldError::InvalidSize),
+        };
+
+        let mut bytes = vec![width, height];
+        bytes.extend(self.mines.to_be_bytes());
+        for y in 0..height {
+            for x in 0..width {
+                if self.is_mine(Coord(x as u16, y as u16)) {
+                    bytes.extend([x, y]);
+                }
+            }
+        }
+        if bytes.len() != 4 + self.mines as usize * 2 {
+            return Err(MinefieldError::MinesNotPlaced);
+        }
+        Ok(bytes)
+    }
+
+    /// Load a minefield from an MBF file, see [Minefield::from_mbf].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be read.
+    /// - Same as [Minefield::from_mbf].
+    pub fn load_mbf<P: AsRef<Path>>(path: P) -> Result<Self, MinefieldError> {
+        let bytes = fs::read(path).map_err(|e| MinefieldError::Io(e.kind()))?;
+        Minefield::from_mbf(&bytes)
+    }
+
+    /// Save the minefield into an MBF file, see [Minefield::to_mbf].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be written.
+    /// - Same as [Minefield::to_mbf].
+    pub fn save_mbf<P: AsRef<Path>>(&self, path: P) -> Result<(), MinefieldError> {
+        fs::write(path, self.to_mbf()?).map_err(|e| MinefieldError::Io(e.kind()))
+    }
+}
@@ -5,10 +5,14 @@
 use std::{fmt::Debug, hash::Hasher};
 
 use arrayvec::ArrayVec;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::events::{EventQueue, MinefieldEvent};
 
 /// Represents a tile coordinate on the minefield.
 #[derive(PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-pub struct Coord<const W: usize, const H: usize>(pub u8, pub u8);
+pub struct Coord<const W: usize, const H: usize>(pub u16, pub u16);
 
 impl<const W: usize, const H: usize> Coord<W, H> {
     /// Returns all possible 8 neighboring coordinates for the given coordinate.
@@ -18,10 +22,14 @@ impl<const W: usize, const H: usize> Coord<W, H> {
         let mut list = ArrayVec::new();
         for y in -1..=1 {
             for x in -1..=1 {
-                let (newx, newy) = (self.0 as i8 + x, self.1 as i8 + y);
-                if newx >= 0 && newy >= 0 && newx < W as i8 && newy < H as i8 && (x != 0 || y != 0)
+                let (newx, newy) = (self.0 as i32 + x, self.1 as i32 + y);
+                if newx >= 0
+                    && newy >= 0
+                    && newx < W as i32
+                    && newy < H as i32
+                    && (x != 0 || y != 0)
                 {
-                    list.push(Coord(newx as u8, newy as u8))
+                    list.push(Coord(newx as u16, newy as u16))
                 }
             }
         }
@@ -30,10 +38,16 @@ impl<const W: usize, const H: usize> Coord<W, H> {
 
     /// Returns a random valid coordinate
     pub fn random() -> Coord<W, H> {
-        Coord(
-            rand::random::<u8>() % (W as u8),
-            rand::random::<u8>() % (H as u8),
-        )
+        Coord::random_with(&mut rand::thread_rng())
+    }
+
+    /// Returns a random valid coordinate drawn from the given random number
+    /// generator. Always draws the x-component before the y-component, so a
+    /// seeded generator always produces the same coordinates.
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R) -> Coord<W, H> {
+        let x = rng.gen_range(0..W as u16);
+        let y = rng.gen_range(0..H as u16);
+        Coord(x, y)
     }
 }
 
@@ -45,7 +59,7 @@ impl<const W: usize, const H: usize> Debug for Coord<W, H> {
 
 impl<const W: usize, const H: usize> std::hash::Hash for Coord<W, H> {
     fn hash<Hash: Hasher>(&self, state: &mut Hash) {
-        state.write_u8(self.1 * W as u8 + self.0);
+        state.write_usize(self.1 as usize * W + self.0 as usize);
     }
 }
 
@@ -58,6 +72,22 @@ pub enum MinefieldError {
     TooManyMines,
     /// Game already ended, unable to performa any actions.
     GameHasEnded,
+    /// The size of the minefield was zero or larger than the minefield can
+    /// hold.
+    InvalidSize,
+    /// The text could not be parsed as a minefield, see
+    /// [crate::text_format].
+    InvalidFormat {
+        /// The line (starting from 1) that could not be parsed
+        line: usize,
+    },
+    /// The bytes could not be loaded as an MBF board, see [crate::mbf].
+    InvalidMbf,
+    /// The mines have not been placed yet, as they are only placed on the
+    /// first reveal, see [GenerationPolicy].
+    MinesNotPlaced,
+    /// Reading or writing a file failed.
+    Io(std::io::ErrorKind),
 }
 
 /// Represents a cell on the "visible" board.
@@ -71,9 +101,29 @@ pub enum Cell {
     Flag,
     /// Hidden tile.
     Hidden,
+    /// Hidden tile marked with a question mark, as the player is unsure
+    /// whether it is a mine. Only possible with
+    /// [Minefield::set_question_marks], and otherwise treated exactly like
+    /// [Cell::Hidden].
+    Question,
     /// Revealed to be a mine, having one in the board always results in a
     /// failed game state.
     Mine,
+    /// The revealed mine that lost the game, only shown in
+    /// [Minefield::final_field].
+    Exploded,
+    /// A flag on a tile without a mine, only shown in
+    /// [Minefield::final_field] after losing.
+    WrongFlag,
+}
+
+impl Cell {
+    /// Whether the cell is hidden and not flagged, so either [Cell::Hidden]
+    /// or [Cell::Question].
+    #[inline]
+    pub fn is_hidden(&self) -> bool {
+        matches!(self, Cell::Hidden | Cell::Question)
+    }
 }
 
 /// Represents the state of the game currently
@@ -87,6 +137,166 @@ pub enum GameState {
     Pending,
 }
 
+/// Determines how mines are placed in relation to the first revealed tile.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum GenerationPolicy {
+    /// Mines are placed entirely randomly before the first reveal, so the first
+    /// reveal may be a mine.
+    #[default]
+    Random,
+    /// Mines are placed on the first reveal, so that the revealed tile is never
+    /// a mine.
+    SafeFirstClick,
+    /// Mines are placed on the first reveal, so that neither the revealed tile
+    /// nor any of its neighbours are a mine, meaning the first reveal is always
+    /// an opening.
+    OpeningFirstClick,
+    /// Mines are placed randomly before the first reveal, but if the first
+    /// reveal is a mine, the mine is moved to the top-left-most free tile, like
+    /// in the classic Windows minesweeper.
+    ClassicRelocation,
+}
+
+impl std::str::FromStr for GenerationPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value.to_lowercase().trim() {
+            "random" => GenerationPolicy::Random,
+            "safe" => GenerationPolicy::SafeFirstClick,
+            "opening" => GenerationPolicy::OpeningFirstClick,
+            "classic" => GenerationPolicy::ClassicRelocation,
+            _ => Err("policy must be either 'random', 'safe', 'opening' or 'classic'")?,
+        })
+    }
+}
+
+impl std::fmt::Display for GenerationPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GenerationPolicy::Random => "random",
+            GenerationPolicy::SafeFirstClick => "safe",
+            GenerationPolicy::OpeningFirstClick => "opening",
+            GenerationPolicy::ClassicRelocation => "classic",
+        })
+    }
+}
+
+/// Decides which tiles are the neighbours of a tile, which in turn decides
+/// the labels of the tiles and which tiles are revealed around empty tiles.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Topology {
+    /// The 8 surrounding tiles on a bounded minefield, like in the classic
+    /// minesweeper.
+    #[default]
+    Moore,
+    /// The 8 surrounding tiles, but the edges of the minefield wrap around to
+    /// the opposite side, as if the minefield was a torus.
+    Toroidal,
+    /// Only the 4 orthogonally adjacent tiles.
+    Orthogonal,
+    /// The 8 tiles that a knight in chess could move to.
+    Knight,
+    /// The 6 surrounding tiles of a hexagonal grid, where every odd row is
+    /// shifted right by half a tile. The tiles above and below a tile are
+    /// therefore the two that it touches, not the three of [Topology::Moore].
+    Hexagonal,
+}
+
+/// Offsets of the neighbours in [Topology::Moore] and [Topology::Toroidal], in
+/// the same order as [Coord::neighbours].
+const MOORE: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+/// Offsets of the neighbours in [Topology::Orthogonal]
+const ORTHOGONAL: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+/// Offsets of the neighbours in [Topology::Knight]
+const KNIGHT: [(i32, i32); 8] = [
+    (-1, -2),
+    (1, -2),
+    (-2, -1),
+    (2, -1),
+    (-2, 1),
+    (2, 1),
+    (-1, 2),
+    (1, 2),
+];
+/// Offsets of the neighbours in [Topology::Hexagonal] for tiles on even rows
+const HEXAGONAL_EVEN: [(i32, i32); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+/// Offsets of the neighbours in [Topology::Hexagonal] for tiles on odd rows,
+/// which are shifted right by half a tile
+const HEXAGONAL_ODD: [(i32, i32); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+impl Topology {
+    /// Returns all the neighbouring coordinates of the given coordinate on a
+    /// minefield of the given size. Never returns the coordinate itself or the
+    /// same neighbour twice, even if the minefield wraps around on itself.
+    pub fn neighbours<const W: usize, const H: usize>(
+        &self,
+        coord: Coord<W, H>,
+        width: u16,
+        height: u16,
+    ) -> ArrayVec<Coord<W, H>, 8> {
+        let offsets: &[(i32, i32)] = match self {
+            Topology::Moore | Topology::Toroidal => &MOORE,
+            Topology::Orthogonal => &ORTHOGONAL,
+            Topology::Knight => &KNIGHT,
+            Topology::Hexagonal if coord.1 % 2 == 1 => &HEXAGONAL_ODD,
+            Topology::Hexagonal => &HEXAGONAL_EVEN,
+        };
+        let (width, height) = (width as i32, height as i32);
+        let mut list = ArrayVec::new();
+        for (x, y) in offsets {
+            let (mut newx, mut newy) = (coord.0 as i32 + x, coord.1 as i32 + y);
+            if *self == Topology::Toroidal {
+                newx = newx.rem_euclid(width);
+                newy = newy.rem_euclid(height);
+            }
+            if newx >= 0 && newy >= 0 && newx < width && newy < height {
+                let neighbour = Coord(newx as u16, newy as u16);
+                if neighbour != coord && !list.contains(&neighbour) {
+                    list.push(neighbour);
+                }
+            }
+        }
+        list
+    }
+}
+
+impl std::str::FromStr for Topology {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(match value.to_lowercase().trim() {
+            "moore" | "classic" => Topology::Moore,
+            "torus" | "toroidal" => Topology::Toroidal,
+            "orthogonal" => Topology::Orthogonal,
+            "knight" => Topology::Knight,
+            "hex" | "hexagonal" => Topology::Hexagonal,
+            _ => Err("topology must be either 'moore', 'torus', 'orthogonal', 'knight' or 'hex'")?,
+        })
+    }
+}
+
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Topology::Moore => "moore",
+            Topology::Toroidal => "torus",
+            Topology::Orthogonal => "orthogonal",
+            Topology::Knight => "knight",
+            Topology::Hexagonal => "hex",
+        })
+    }
+}
+
 /// Generic struct for a 2D matrix of type T
 #[derive(Debug, PartialEq, Clone, Eq, Copy)]
 pub struct Matrix<T, const W: usize, const H: usize>(pub [[T; W]; H]);
@@ -139,17 +349,94 @@ impl<T, const W: usize, const H: usize> IntoIterator for Matrix<T, W, H> {
     }
 }
 
-/// Represents a mechanical abstract minefield in minesweeper
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Largest width and height that a [CustomMinefield] can have.
+pub const CUSTOM_MAX_SIZE: usize = 100;
+
+/// Minefield that can be of any size up to [CUSTOM_MAX_SIZE] in both
+/// directions, the size is decided at runtime with
+/// [Minefield::generate_sized].
+pub type CustomMinefield = Minefield<CUSTOM_MAX_SIZE, CUSTOM_MAX_SIZE>;
+
+/// Represents a mechanical abstract minefield in minesweeper.
+///
+/// `W` and `H` are the largest possible size of the minefield, but the actual
+/// size of the minefield can be smaller, see [Minefield::generate_sized]. Tiles
+/// outside of the actual size are always [Cell::Empty] and never mines.
+#[derive(Debug)]
 pub struct Minefield<const W: usize, const H: usize> {
     mine_indices: Matrix<bool, W, H>,
     /// The visible field
     pub field: Matrix<Cell, W, H>,
+    width: u16,
+    height: u16,
     /// How many mines are in the field.
-    pub mines: u8,
+    pub mines: u16,
     game_state: GameState,
+    /// How many tiles without a mine have been revealed, so that victory is
+    /// decided without scanning the whole field.
+    revealed: u32,
+    /// How many mines have been revealed.
+    revealed_mines: u32,
+    /// How many tiles are flagged.
+    flags: u16,
+    seed: Option<u64>,
+    policy: GenerationPolicy,
+    topology: Topology,
+    question_marks: bool,
+    /// Whether the first reveal is yet to happen, mines might not be placed
+    /// before it depending on the [GenerationPolicy].
+    before_first_reveal: bool,
+    /// The events that are not read yet by every subscription, see
+    /// [crate::events].
+    pub(crate) events: Option<EventQueue<W, H>>,
+}
+
+impl<const W: usize, const H: usize> Clone for Minefield<W, H> {
+    /// Clone the minefield without its events or subscriptions, see
+    /// [crate::events].
+    fn clone(&self) -> Self {
+        Minefield {
+            mine_indices: self.mine_indices,
+            field: self.field,
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            game_state: self.game_state,
+            revealed: self.revealed,
+            revealed_mines: self.revealed_mines,
+            flags: self.flags,
+            seed: self.seed,
+            policy: self.policy,
+            topology: self.topology,
+            question_marks: self.question_marks,
+            before_first_reveal: self.before_first_reveal,
+            events: None,
+        }
+    }
 }
 
+impl<const W: usize, const H: usize> PartialEq for Minefield<W, H> {
+    /// Compare the minefields, ignoring their events, see [crate::events].
+    fn eq(&self, other: &Self) -> bool {
+        self.mine_indices == other.mine_indices
+            && self.field == other.field
+            && self.width == other.width
+            && self.height == other.height
+            && self.mines == other.mines
+            && self.game_state == other.game_state
+            && self.revealed == other.revealed
+            && self.revealed_mines == other.revealed_mines
+            && self.flags == other.flags
+            && self.seed == other.seed
+            && self.policy == other.policy
+            && self.topology == other.topology
+            && self.question_marks == other.question_marks
+            && self.before_first_reveal == other.before_first_reveal
+    }
+}
+
+impl<const W: usize, const H: usize> Eq for Minefield<W, H> {}
+
 /// Represents a reveal, returned by reveal and flag functions
 pub type Reveal<const W: usize, const H: usize> = (Coord<W, H>, Cell);
 
@@ -158,27 +445,150 @@ impl<const W: usize, const H: usize> Minefield<W, H> {
     ///
     /// # Errors
     /// - [MinefieldError::TooManyMines] if the amount of mines is too large.
-    pub fn generate(mines: u8) -> Result<Self, MinefieldError> {
-        let mut mine_indices = Matrix([[false; W]; H]);
-        if mines as usize > W * H {
+    pub fn generate(mines: u16) -> Result<Self, MinefieldError> {
+        Minefield::generate_with_rng(mines, &mut rand::thread_rng())
+    }
+
+    /// Generate a new minefield with the provided amount of mines, where the
+    /// seed for the minefield is drawn from the given random number generator.
+    ///
+    /// # Errors
+    /// - [MinefieldError::TooManyMines] if the amount of mines is too large.
+    pub fn generate_with_rng<R: Rng + ?Sized>(
+        mines: u16,
+        rng: &mut R,
+    ) -> Result<Self, MinefieldError> {
+        Minefield::generate_with_seed(mines, rng.gen())
+    }
+
+    /// Generate a new minefield with the provided amount of mines from the
+    /// given seed. The same seed will always result in the same minefield,
+    /// regardless of the platform.
+    ///
+    /// # Errors
+    /// - [MinefieldError::TooManyMines] if the amount of mines is too large.
+    pub fn generate_with_seed(mines: u16, seed: u64) -> Result<Self, MinefieldError> {
+        Minefield::generate_with_policy(mines, seed, GenerationPolicy::Random)
+    }
+
+    /// Generate a new minefield with the provided amount of mines from the
+    /// given seed, where the mines are placed according to the given
+    /// [GenerationPolicy]. The same seed, policy and first reveal will always
+    /// result in the same minefield.
+    ///
+    /// # Errors
+    /// - [MinefieldError::TooManyMines] if the amount of mines is too large for
+    ///   the minefield and the policy.
+    pub fn generate_with_policy(
+        mines: u16,
+        seed: u64,
+        policy: GenerationPolicy,
+    ) -> Result<Self, MinefieldError> {
+        Minefield::generate_sized(W as u16, H as u16, mines, seed, policy)
+    }
+
+    /// Generate a new minefield of the given size, which can be smaller than
+    /// the const-generic size of the minefield. Otherwise works exactly like
+    /// [Minefield::generate_with_policy], so a minefield of the same size,
+    /// seed and policy always has the same mines, regardless of how large the
+    /// const-generic size is.
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if width or height is zero or larger
+    ///   than the const-generic size of the minefield.
+    /// - [MinefieldError::TooManyMines] if the amount of mines is too large for
+    ///   the minefield and the policy.
+    pub fn generate_sized(
+        width: u16,
+        height: u16,
+        mines: u16,
+        seed: u64,
+        policy: GenerationPolicy,
+    ) -> Result<Self, MinefieldError> {
+        let reserved = match policy {
+            GenerationPolicy::Random => 0,
+            GenerationPolicy::SafeFirstClick | GenerationPolicy::ClassicRelocation => 1,
+            GenerationPolicy::OpeningFirstClick => 9,
+        };
+        let area = width as usize * height as usize;
+        if width == 0 || height == 0 || width as usize > W || height as usize > H {
+            Err(MinefieldError::InvalidSize)
+        } else if mines as usize + reserved.min(area) > area {
             Err(MinefieldError::TooManyMines)
         } else {
-            for _ in 0..mines {
-                let mut coord = Coord::<W, H>::random();
-                while mine_indices.get(coord) {
-                    coord = Coord::random();
+            let mut field = Matrix([[Cell::Empty; W]; H]);
+            for y in 0..height {
+                for x in 0..width {
+                    field.set(Coord(x, y), Cell::Hidden);
                 }
-                mine_indices.set(coord, true);
             }
-
-            Ok(Minefield {
-                mine_indices,
-                field: Matrix([[Cell::Hidden; W]; H]),
+            let mut minefield = Minefield {
+                mine_indices: Matrix([[false; W]; H]),
+                field,
+                width,
+                height,
                 mines,
                 game_state: GameState::Pending,
-            })
+                revealed: 0,
+                revealed_mines: 0,
+                flags: 0,
+                seed: Some(seed),
+                policy,
+                topology: Topology::default(),
+                question_marks: false,
+                before_first_reveal: true,
+                events: None,
+            };
+            if let GenerationPolicy::Random | GenerationPolicy::ClassicRelocation = policy {
+                minefield.place_mines(&[]);
+            }
+            Ok(minefield)
+        }
+    }
+
+    /// Place the mines randomly from the seed of the minefield, so that none
+    /// of the mines are placed in the excluded coordinates.
+    fn place_mines(&mut self, excluded: &[Coord<W, H>]) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed.unwrap_or_default());
+        let (width, height) = (self.width, self.height);
+        let random =
+            |rng: &mut ChaCha8Rng| Coord(rng.gen_range(0..width), rng.gen_range(0..height));
+        for _ in 0..self.mines {
+            let mut coord = random(&mut rng);
+            while self.mine_indices.get(coord) || excluded.contains(&coord) {
+                coord = random(&mut rng);
+            }
+            self.mine_indices.set(coord, true);
+        }
+    }
+
+    /// Apply the [GenerationPolicy] of the minefield for the first reveal at
+    /// the given coordinate.
+    fn prepare_first_reveal(&mut self, coord: Coord<W, H>) {
+        match self.policy {
+            GenerationPolicy::Random => {}
+            GenerationPolicy::SafeFirstClick => self.place_mines(&[coord]),
+            GenerationPolicy::OpeningFirstClick => {
+                let mut excluded = self.neighbours(coord).to_vec();
+                excluded.push(coord);
+                self.place_mines(&excluded);
+            }
+            GenerationPolicy::ClassicRelocation => {
+                if self.is_mine(coord) {
+                    let width = self.width;
+                    let free = (0..self.height)
+                        .flat_map(|y| (0..width).map(move |x| Coord(x, y)))
+                        .find(|c| !self.is_mine(*c) && *c != coord);
+                    if let Some(free) = free {
+                        self.mine_indices.set(coord, false);
+                        self.mine_indices.set(free, true);
+                    }
+                }
+            }
         }
+        self.before_first_reveal = false;
     }
+
     /// Generate a new minefield with the provided amount of mines.
     ///
     /// # Errors
@@ -187,36 +597,166 @@ impl<const W: usize, const H: usize> Minefield<W, H> {
         Minefield {
             mine_indices: mines,
             field: Matrix([[Cell::Hidden; W]; H]),
+            width: W as u16,
+            height: H as u16,
             mines: mines
                 .into_iter()
-                .map(|row| row.iter().filter(|i| **i).count() as u8)
+                .map(|row| row.iter().filter(|i| **i).count() as u16)
                 .sum(),
             game_state: GameState::Pending,
+            revealed: 0,
+            revealed_mines: 0,
+            flags: 0,
+            seed: None,
+            policy: GenerationPolicy::Random,
+            topology: Topology::default(),
+            question_marks: false,
+            before_first_reveal: true,
+            events: None,
         }
     }
 
+    /// Return the actual width of the minefield, which can be smaller than
+    /// `W`.
+    #[inline]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Return the actual height of the minefield, which can be smaller than
+    /// `H`.
+    #[inline]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Whether the coordinate is within the actual size of the minefield.
+    #[inline]
+    pub fn contains(&self, coord: Coord<W, H>) -> bool {
+        coord.0 < self.width && coord.1 < self.height
+    }
+
+    /// Returns all the neighbouring coordinates of the given coordinate that
+    /// are within the actual size of the minefield, according to the
+    /// [Topology] of the minefield.
+    pub fn neighbours(&self, coord: Coord<W, H>) -> ArrayVec<Coord<W, H>, 8> {
+        self.topology.neighbours(coord, self.width, self.height)
+    }
+
+    /// Return the topology of the minefield, which decides the neighbours of
+    /// every tile.
+    #[inline]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Change the topology of the minefield. Should be done before the first
+    /// reveal, as the labels of already revealed tiles are not updated.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    /// Whether flagging a tile cycles through [Cell::Question] as well.
+    #[inline]
+    pub fn question_marks(&self) -> bool {
+        self.question_marks
+    }
+
+    /// Enable or disable question marks, see [Minefield::flag].
+    pub fn set_question_marks(&mut self, question_marks: bool) {
+        self.question_marks = question_marks;
+    }
+
+    /// Return the seed this minefield was generated from, None if the
+    /// minefield was not generated from a seed, see [Minefield::with_mines].
+    #[inline]
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Return the [GenerationPolicy] the mines of this minefield are placed
+    /// with.
+    #[inline]
+    pub fn policy(&self) -> GenerationPolicy {
+        self.policy
+    }
+
+    /// Return how many tiles are flagged, whether there is a mine under the
+    /// flag or not.
+    #[inline]
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Return how many mines are left after subtracting the flags from the
+    /// mines, as shown on the counter of the classic minesweeper. Negative if
+    /// there are more flags than mines.
+    #[inline]
+    pub fn remaining_mines(&self) -> i32 {
+        self.mines as i32 - self.flags as i32
+    }
+
     /// Return the current state of the game immutably.
     #[inline]
     pub fn game_state(&self) -> GameState {
         self.game_state
     }
 
-    /// Update the current state of the game.
+    /// Update the current state of the game from the counters of revealed
+    /// tiles.
     #[inline]
     fn update_game_state(&mut self) {
-        self.game_state = if self.field.iter().flatten().any(|c| *c == Cell::Mine) {
+        let safe_tiles = self.width as u32 * self.height as u32 - self.mines as u32;
+        let previous = self.game_state;
+        self.game_state = if self.revealed_mines > 0 {
             GameState::GameOver
-        } else if self
-            .field
-            .into_iter()
-            .flatten()
-            .zip(self.mine_indices.iter().flatten())
-            .all(|(c, is_mine)| (c == Cell::Hidden || c == Cell::Flag) == *is_mine)
-        {
+        } else if self.revealed >= safe_tiles {
             GameState::Victory
         } else {
             GameState::Pending
         };
+        if self.game_state != previous {
+            self.emit_game_state();
+        }
+    }
+
+    /// Emit the event of the game ending, if it has ended.
+    fn emit_game_state(&mut self) {
+        match self.game_state {
+            GameState::Victory => self.emit(MinefieldEvent::GameWon),
+            GameState::GameOver => self.emit(MinefieldEvent::GameLost),
+            GameState::Pending => {}
+        }
+    }
+
+    /// Update the counters of revealed and flagged tiles when a tile changes
+    /// from `before` to `after`.
+    #[inline]
+    fn track(&mut self, before: Cell, after: Cell) {
+        let counts = |cell| match cell {
+            Cell::Empty | Cell::Label(_) => (1, 0, 0),
+            Cell::Mine => (0, 1, 0),
+            Cell::Flag => (0, 0, 1),
+            _ => (0, 0, 0),
+        };
+        let (before, after) = (counts(before), counts(after));
+        self.revealed = self.revealed + after.0 - before.0;
+        self.revealed_mines = self.revealed_mines + after.1 - before.1;
+        self.flags = self.flags + after.2 - before.2;
+    }
+
+    /// Count the revealed and flagged tiles of the whole field again, used when the field
+    /// is set directly.
+    fn recount(&mut self) {
+        self.revealed = 0;
+        self.revealed_mines = 0;
+        self.flags = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.track(Cell::Hidden, self.field.get(Coord(x, y)));
+            }
+        }
+        self.update_game_state();
     }
 
     /// Attempts to reveal a tile.
@@ -225,47 +765,88 @@ impl<const W: usize, const H: usize> Minefield<W, H> {
     /// - [MinefieldError::GameHasEnded] if the game is already over
     /// - [MinefieldError::InvalidCoordinate] if the attempted coordinate was not valid.
     pub fn reveal(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        if self.before_first_reveal && self.contains(coord) {
+            self.prepare_first_reveal(coord);
+        }
         let mut reveals = Vec::new();
-        self._reveal(coord, true, &mut reveals)?;
+        self._reveal(coord, &mut reveals)?;
+        self.update_game_state();
         Ok(reveals)
     }
 
-    /// Private reveal function that contains `update_state`-parameter simply to
-    /// help with recursion, literally halving the amount of time that reveal
-    /// would otherwise take.
+    /// Private reveal function that does not update the state of the game, so
+    /// that chords can reveal several tiles before updating it. Openings are
+    /// flood filled with a stack instead of recursion, so even the largest
+    /// openings can not overflow the call stack. Tiles are still revealed in
+    /// the same depth-first order as recursion would.
     fn _reveal(
         &mut self,
         coord: Coord<W, H>,
-        update_state: bool,
         reveals: &mut Vec<Reveal<W, H>>,
     ) -> Result<(), MinefieldError> {
         if self.game_state() != GameState::Pending {
-            Err(MinefieldError::GameHasEnded)
-        } else if coord.0 >= (W as u8) || coord.1 >= (H as u8) {
-            Err(MinefieldError::InvalidCoordinate)
-        } else {
+            return Err(MinefieldError::GameHasEnded);
+        } else if !self.contains(coord) {
+            return Err(MinefieldError::InvalidCoordinate);
+        }
+        let mut stack = vec![coord];
+        while let Some(coord) = stack.pop() {
             let field_cell = self.field.get(coord);
-            if field_cell == Cell::Flag || field_cell == Cell::Hidden {
+            if field_cell == Cell::Flag || field_cell.is_hidden() {
                 let cell = self.cell_contents(coord);
                 self.field.set(coord, cell);
+                self.track(field_cell, cell);
+                self.emit(match cell {
+                    Cell::Mine => MinefieldEvent::MineExploded(coord),
+                    cell => MinefieldEvent::Revealed(coord, cell),
+                });
                 reveals.push((coord, cell));
                 if cell == Cell::Empty {
-                    for neighbor in coord.neighbours() {
-                        match self._reveal(neighbor, false, reveals) {
-                            Err(MinefieldError::GameHasEnded) => break,
-                            e => e?,
-                        };
-                    }
+                    stack.extend(self.neighbours(coord).into_iter().rev());
                 }
-                if update_state {
+            }
+        }
+        Ok(())
+    }
+
+    /// Attempts to chord a tile, meaning that if the tile is a label with as
+    /// many flagged neighbours as the label says, every hidden neighbour that
+    /// is not flagged is revealed. If any of the flags were wrong, this results
+    /// in a lost game. Does nothing if the label is not satisfied.
+    ///
+    /// # Errors
+    /// - [MinefieldError::GameHasEnded] if the game is already over
+    /// - [MinefieldError::InvalidCoordinate] if the attempted coordinate was not valid.
+    pub fn chord(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
+        if self.game_state() != GameState::Pending {
+            Err(MinefieldError::GameHasEnded)
+        } else if !self.contains(coord) {
+            Err(MinefieldError::InvalidCoordinate)
+        } else {
+            let mut reveals = Vec::new();
+            if let Cell::Label(label) = self.field.get(coord) {
+                let neighbours = self.neighbours(coord);
+                let flags = neighbours
+                    .iter()
+                    .filter(|c| self.field.get(**c) == Cell::Flag)
+                    .count();
+                if flags == label as usize {
+                    for neighbour in neighbours {
+                        if self.field.get(neighbour).is_hidden() {
+                            self._reveal(neighbour, &mut reveals)?;
+                        }
+                    }
                     self.update_game_state();
                 }
             }
-            Ok(())
+            Ok(reveals)
         }
     }
 
-    /// Attempts to flag a tile.
+    /// Attempts to flag a tile. Flagging a flagged tile removes the flag, or
+    /// with [Minefield::set_question_marks] marks it with a question mark,
+    /// which is removed on the next flag instead. Returns the new cell of the
+    /// tile, or nothing if the tile is already revealed.
     ///
     /// # Errors
     /// - [MinefieldError::GameHasEnded] if the game is already over
@@ -273,27 +854,97 @@ impl<const W: usize, const H: usize> Minefield<W, H> {
     pub fn flag(&mut self, coord: Coord<W, H>) -> Result<Vec<Reveal<W, H>>, MinefieldError> {
         if self.game_state() != GameState::Pending {
             Err(MinefieldError::GameHasEnded)
-        } else if coord.0 >= (W as u8) || coord.1 >= (H as u8) {
+        } else if !self.contains(coord) {
             Err(MinefieldError::InvalidCoordinate)
         } else {
-            self.field.set(
-                coord,
-                match self.field.get(coord) {
-                    Cell::Flag => Cell::Hidden,
-                    Cell::Hidden => Cell::Flag,
-                    c => c,
-                },
-            );
-            Ok(Vec::new())
+            let before = self.field.get(coord);
+            let after = match before {
+                Cell::Flag if self.question_marks => Cell::Question,
+                Cell::Question if self.question_marks => Cell::Hidden,
+                Cell::Flag => Cell::Hidden,
+                Cell::Hidden | Cell::Question => Cell::Flag,
+                _ => return Ok(Vec::new()),
+            };
+            self.field.set(coord, after);
+            self.track(before, after);
+            match before {
+                Cell::Flag => self.emit(MinefieldEvent::FlagRemoved(coord)),
+                Cell::Question => self.emit(MinefieldEvent::QuestionRemoved(coord)),
+                _ => {}
+            }
+            match after {
+                Cell::Flag => self.emit(MinefieldEvent::FlagPlaced(coord)),
+                Cell::Question => self.emit(MinefieldEvent::QuestionPlaced(coord)),
+                _ => {}
+            }
+            Ok(vec![(coord, after)])
+        }
+    }
+
+    /// Return the field as it is shown once the game has ended, so that the
+    /// player can see what the minefield really was. After a loss every mine
+    /// is shown as [Cell::Mine], the revealed mines that lost the game as
+    /// [Cell::Exploded] and the flags without a mine as [Cell::WrongFlag].
+    /// After a victory every mine is shown flagged. While the game is pending
+    /// this is the same as [Minefield::field].
+    pub fn final_field(&self) -> Matrix<Cell, W, H> {
+        let mut field = self.field;
+        if self.game_state == GameState::Pending {
+            return field;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let coord = Coord(x, y);
+                let cell = match (self.field.get(coord), self.is_mine(coord)) {
+                    (_, true) if self.game_state == GameState::Victory => Cell::Flag,
+                    (Cell::Mine, _) => Cell::Exploded,
+                    (Cell::Flag, false) => Cell::WrongFlag,
+                    (cell, true) if cell.is_hidden() => Cell::Mine,
+                    (cell, _) => cell,
+                };
+                field.set(coord, cell);
+            }
+        }
+        field
+    }
+
+    /// Form a minefield of the given size directly from the mines and the
+    /// visible field, used for loading minefields in [crate::text_format] and
+    /// [crate::mbf].
+    pub(crate) fn from_parts(
+        width: u16,
+        height: u16,
+        mines: Matrix<bool, W, H>,
+        field: Matrix<Cell, W, H>,
+    ) -> Self {
+        let mut minefield = Minefield::with_mines(mines);
+        minefield.width = width;
+        minefield.height = height;
+        minefield.field = field;
+        minefield.recount();
+        minefield
+    }
+
+    /// Set the given tiles and the state of the game directly, used for
+    /// undoing and redoing actions in [crate::history::History].
+    pub(crate) fn restore(&mut self, cells: &[Reveal<W, H>], game_state: GameState) {
+        for (coord, cell) in cells {
+            self.track(self.field.get(*coord), *cell);
+            self.field.set(*coord, *cell);
+            self.emit(MinefieldEvent::Restored(*coord, *cell));
+        }
+        if self.game_state != game_state {
+            self.game_state = game_state;
+            self.emit_game_state();
         }
     }
 
-    fn cell_contents(&self, coord: Coord<W, H>) -> Cell {
+    pub(crate) fn cell_contents(&self, coord: Coord<W, H>) -> Cell {
         if self.is_mine(coord) {
             Cell::Mine
         } else {
-            let mines = coord
-                .neighbours()
+            let mines = self
+                .neighbours(coord)
                 .iter()
                 .filter(|c| self.is_mine(**c))
                 .count() as u8;
@@ -305,7 +956,7 @@ impl<const W: usize, const H: usize> Minefield<W, H> {
         }
     }
 
-    fn is_mine(&self, coord: Coord<W, H>) -> bool {
+    pub(crate) fn is_mine(&self, coord: Coord<W, H>) -> bool {
         self.mine_indices.get(coord)
     }
 }
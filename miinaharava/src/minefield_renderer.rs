@@ -1,12 +1,13 @@
 use sdl2::{
-    rect::Rect,
-    render::{Canvas, Texture, TextureCreator},
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{BlendMode, Canvas, Texture, TextureCreator},
     rwops::RWops,
     surface::Surface,
     video::{Window, WindowContext},
 };
 
-use crate::minefield::{Cell, Coord, Minefield};
+use crate::minefield::{Cell, Coord, Matrix, Minefield, Topology};
 
 static ATLAS_BYTES: &[u8] = include_bytes!("./resources/atlas.bmp");
 const SIZE: u32 = 16;
@@ -34,56 +35,279 @@ impl<'a> MinefieldRenderer<'a> {
         canvas: &mut Canvas<Window>,
         hover_tile: Option<Coord<W, H>>,
     ) {
-        let (pos_x, pos_y, total_w, total_h) = self.get_target::<W, H>().into();
-        let (w, h) = (total_w / W as u32, total_h / H as u32);
-        for y in 0..(H as u8) {
-            for x in 0..(W as u8) {
-                let dest_rect = Rect::new(
-                    pos_x + (x as u32 * w) as i32,
-                    pos_y + (y as u32 * h) as i32,
-                    w,
-                    h,
+        let field = minefield.final_field();
+        for coord in coords(minefield) {
+            let hover = hover_tile.as_ref().map(|h| h == &coord).unwrap_or(false);
+            let (sx, sy, sw, sh) = source(field.get(coord), hover);
+            let dest_rect = self.tile_rect(minefield, coord);
+            for span in self.tile_spans(minefield, coord) {
+                // The part of the sprite that is drawn on this span of the tile
+                let source_rect = Rect::new(
+                    sx + (span.x() - dest_rect.x()) * sw as i32 / dest_rect.width() as i32,
+                    sy + (span.y() - dest_rect.y()) * sh as i32 / dest_rect.height() as i32,
+                    (span.width() * sw / dest_rect.width()).max(1),
+                    (span.height() * sh / dest_rect.height()).max(1),
                 );
-                let coord = Coord::<W, H>(x, y);
-                let hover = hover_tile.as_ref().map(|h| h == &coord).unwrap_or(false);
-                let source_rect = Rect::from(source(minefield.field.get(coord), hover));
-                canvas.copy(&self.atlas, source_rect, dest_rect).unwrap();
+                canvas.copy(&self.atlas, source_rect, span).unwrap();
+            }
+        }
+        if is_hexagonal(minefield) {
+            canvas.set_draw_color(Color::RGB(0x40, 0x40, 0x40));
+            for coord in coords(minefield) {
+                let outline = hex_outline(self.tile_rect(minefield, coord), 0);
+                canvas.draw_lines(&outline[..]).unwrap();
+            }
+        }
+    }
+
+    /// Blend the given colour over every tile that has one, after the tiles
+    /// themselves have been drawn with [MinefieldRenderer::draw].
+    pub fn draw_overlay<const W: usize, const H: usize>(
+        &self,
+        minefield: &Minefield<W, H>,
+        canvas: &mut Canvas<Window>,
+        overlay: &Matrix<Option<Color>, W, H>,
+    ) {
+        let blend_mode = canvas.blend_mode();
+        canvas.set_blend_mode(BlendMode::Blend);
+        for coord in coords(minefield) {
+            if let Some(color) = overlay.get(coord) {
+                canvas.set_draw_color(color);
+                canvas
+                    .fill_rects(&self.tile_spans(minefield, coord))
+                    .unwrap();
+            }
+        }
+        canvas.set_blend_mode(blend_mode);
+    }
+
+    /// Draw a red border around the given tile.
+    pub fn draw_marker<const W: usize, const H: usize>(
+        &self,
+        minefield: &Minefield<W, H>,
+        canvas: &mut Canvas<Window>,
+        marker: Coord<W, H>,
+    ) {
+        let dest_rect = self.tile_rect(minefield, marker);
+        let size = dest_rect.width();
+        canvas.set_draw_color(Color::RGB(0xFF, 0, 0));
+        for i in 0..(size / 8).max(1) {
+            if is_hexagonal(minefield) {
+                let outline = hex_outline(dest_rect, i as i32);
+                canvas.draw_lines(&outline[..]).unwrap();
+            } else {
+                let (i, inset) = (i as i32, 2 * i);
+                let rect = Rect::new(
+                    dest_rect.x() + i,
+                    dest_rect.y() + i,
+                    size.saturating_sub(inset),
+                    size.saturating_sub(inset),
+                );
+                canvas.draw_rect(rect).unwrap();
             }
         }
     }
 
     pub fn get_coord<const W: usize, const H: usize>(
         &self,
+        minefield: &Minefield<W, H>,
         mouse: (i32, i32),
     ) -> Option<Coord<W, H>> {
-        let (pos_x, pos_y, total_w, total_h) = self.get_target::<W, H>().into();
-        let (w, h) = (total_w as i32 / W as i32, total_h as i32 / H as i32);
-        let x = (mouse.0 - pos_x) / w;
-        let y = (mouse.1 - pos_y) / h;
-        if x >= 0 && x < W as i32 && y >= 0 && y < H as i32 {
-            Some(Coord(x as u8, y as u8))
+        let (width, height) = (minefield.width() as i32, minefield.height() as i32);
+        let (pos_x, pos_y, _, _) = self.get_target(minefield).into();
+        let size = self.tile_size(minefield) as i32;
+        let y = (mouse.1 - pos_y).div_euclid(size);
+        if !is_hexagonal(minefield) {
+            let x = (mouse.0 - pos_x).div_euclid(size);
+            return if x >= 0 && x < width && y >= 0 && y < height {
+                Some(Coord(x as u16, y as u16))
+            } else {
+                None
+            };
+        }
+
+        // Hexagons reach into the rows above and below them, so the tiles
+        // around the square the mouse is on are checked as well
+        for y in (y - 1..=y + 1).filter(|y| *y >= 0 && *y < height) {
+            let x =
+                (mouse.0 - pos_x - row_shift(minefield, y as u16, size as u32)).div_euclid(size);
+            for x in (x - 1..=x + 1).filter(|x| *x >= 0 && *x < width) {
+                let coord = Coord(x as u16, y as u16);
+                if hex_contains(self.tile_rect(minefield, coord), mouse) {
+                    return Some(coord);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the area of the window the minefield is drawn on. On a
+    /// hexagonal minefield the odd rows are shifted right by half a tile, and
+    /// the tiles of the last row reach a quarter of a tile lower, so the area
+    /// is that much larger.
+    pub fn get_target<const W: usize, const H: usize>(&self, minefield: &Minefield<W, H>) -> Rect {
+        let size = self.tile_size(minefield);
+        let (width, height) = (minefield.width() as u32, minefield.height() as u32);
+        let (shift, cap) = if is_hexagonal(minefield) {
+            (size / 2, size / 4)
         } else {
-            None
+            (0, 0)
+        };
+        Rect::from_center(
+            self.target.center(),
+            width * size + shift,
+            height * size + cap,
+        )
+    }
+
+    /// The area of the window the given tile is drawn on. Hexagonal tiles are
+    /// a quarter of a tile taller than they are wide, as they overlap with the
+    /// rows above and below them.
+    fn tile_rect<const W: usize, const H: usize>(
+        &self,
+        minefield: &Minefield<W, H>,
+        coord: Coord<W, H>,
+    ) -> Rect {
+        let (pos_x, pos_y, _, _) = self.get_target(minefield).into();
+        let size = self.tile_size(minefield);
+        let cap = if is_hexagonal(minefield) { size / 4 } else { 0 };
+        Rect::new(
+            pos_x + row_shift(minefield, coord.1, size) + (coord.0 as u32 * size) as i32,
+            pos_y + (coord.1 as u32 * size) as i32,
+            size,
+            size + cap,
+        )
+    }
+
+    /// The rows of pixels that make up the shape of the given tile, which is
+    /// the whole [MinefieldRenderer::tile_rect] for square tiles. Hexagonal
+    /// tiles are pointy at the top and the bottom, so every row of the pointy
+    /// parts is narrower the closer it is to the point. Rows of the same width
+    /// are combined into one.
+    fn tile_spans<const W: usize, const H: usize>(
+        &self,
+        minefield: &Minefield<W, H>,
+        coord: Coord<W, H>,
+    ) -> Vec<Rect> {
+        let rect = self.tile_rect(minefield, coord);
+        if !is_hexagonal(minefield) {
+            return vec![rect];
+        }
+        let mut spans: Vec<Rect> = Vec::new();
+        for y in rect.top()..rect.bottom() {
+            // The pixels whose centers are within the hexagon, see hex_width
+            let center = 2 * rect.x() + rect.width() as i32;
+            let width = hex_width(rect, y);
+            let left = (center - width).div_euclid(2);
+            let right = (center + width - 1).div_euclid(2);
+            let span = Rect::new(left, y, (right - left + 1) as u32, 1);
+            match spans.last_mut() {
+                Some(last) if last.x() == span.x() && last.width() == span.width() => {
+                    last.set_height(last.height() + 1)
+                }
+                _ => spans.push(span),
+            }
         }
+        spans
     }
 
-    pub fn get_target<const W: usize, const H: usize>(&self) -> Rect {
-        let w = self.target.width() as usize / W;
-        let h = self.target.height() as usize / H;
-        let scale = w.min(h);
-        Rect::from_center(self.target.center(), (W * scale) as u32, (H * scale) as u32)
+    /// Size of a single tile in pixels, so that the whole minefield fits in
+    /// the target area
+    fn tile_size<const W: usize, const H: usize>(&self, minefield: &Minefield<W, H>) -> u32 {
+        if !is_hexagonal(minefield) {
+            let w = self.target.width() / minefield.width() as u32;
+            let h = self.target.height() / minefield.height() as u32;
+            return w.min(h);
+        }
+        // Count in half tiles horizontally, since hexagonal rows are shifted
+        // by half a tile, and in quarter tiles vertically, since the last row
+        // reaches a quarter of a tile lower
+        let w = self.target.width() * 2 / (minefield.width() as u32 * 2 + 1);
+        let h = self.target.height() * 4 / (minefield.height() as u32 * 4 + 1);
+        // The sloped sides of neighbouring hexagons only meet exactly when
+        // the tiles are a multiple of 4 pixels wide
+        (w.min(h) / 4 * 4).max(4)
     }
 }
 
+/// Every coordinate within the actual size of the minefield
+fn coords<const W: usize, const H: usize>(
+    minefield: &Minefield<W, H>,
+) -> impl Iterator<Item = Coord<W, H>> {
+    let (width, height) = (minefield.width(), minefield.height());
+    (0..height).flat_map(move |y| (0..width).map(move |x| Coord(x, y)))
+}
+
+/// Whether the tiles of the minefield are drawn as hexagons, where the odd
+/// rows are shifted by half a tile so that every tile touches exactly its six
+/// neighbours.
+fn is_hexagonal<const W: usize, const H: usize>(minefield: &Minefield<W, H>) -> bool {
+    minefield.topology() == Topology::Hexagonal && minefield.height() > 1
+}
+
+/// How many pixels the given row is shifted to the right
+fn row_shift<const W: usize, const H: usize>(
+    minefield: &Minefield<W, H>,
+    y: u16,
+    size: u32,
+) -> i32 {
+    if is_hexagonal(minefield) && y % 2 == 1 {
+        (size / 2) as i32
+    } else {
+        0
+    }
+}
+
+/// The corners of the hexagon drawn in the given tile area, moved inwards by
+/// `inset` pixels, with the first corner repeated at the end so that the
+/// lines between them make a closed outline.
+fn hex_outline(rect: Rect, inset: i32) -> [Point; 7] {
+    let (left, right) = (rect.left() + inset, rect.right() - 1 - inset);
+    let (top, bottom) = (rect.top() + inset, rect.bottom() - 1 - inset);
+    let cap = (rect.height() - rect.width()) as i32;
+    let center = rect.center().x();
+    [
+        Point::new(center, top),
+        Point::new(right, rect.top() + cap),
+        Point::new(right, rect.bottom() - 1 - cap),
+        Point::new(center, bottom),
+        Point::new(left, rect.bottom() - 1 - cap),
+        Point::new(left, rect.top() + cap),
+        Point::new(center, top),
+    ]
+}
+
+/// Twice the width of the hexagon drawn in the given tile area on the given
+/// row of pixels, measured at the center of the row. The hexagon is as wide as
+/// the tile in the middle, and its sloped sides meet at the top and the bottom
+/// of the area. Doubled so that it stays a whole number.
+fn hex_width(rect: Rect, y: i32) -> i32 {
+    let size = rect.width() as i32;
+    let cap = rect.height() as i32 - size;
+    let dy = (2 * y + 1 - (2 * rect.y() + size + cap)).abs();
+    size.min(2 * (size + cap) - 2 * dy)
+}
+
+/// Whether the center of the given pixel is within the hexagon drawn in the
+/// given tile area, see [hex_width].
+fn hex_contains(rect: Rect, (x, y): (i32, i32)) -> bool {
+    let dx = (2 * x + 1 - (2 * rect.x() + rect.width() as i32)).abs();
+    rect.contains_point((x, y)) && dx <= hex_width(rect, y)
+}
+
 const fn source(cell: Cell, hover: bool) -> (i32, i32, u32, u32) {
     let pos = match cell {
         Cell::Hidden if hover => (0, 3),
         Cell::Flag if hover => (1, 3),
+        Cell::Question if hover => (3, 3),
 
         Cell::Empty => (0, 0),
         Cell::Hidden => (1, 0),
         Cell::Flag => (2, 0),
+        Cell::Question => (2, 3),
         Cell::Mine => (3, 0),
+        Cell::Exploded => (0, 4),
+        Cell::WrongFlag => (1, 4),
         Cell::Label(x) => (((x - 1) % 4) as i32, ((x - 1) / 4 + 1) as i32),
     };
     (pos.0 * STRIDE, pos.1 * STRIDE, SIZE, SIZE)
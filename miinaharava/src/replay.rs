@@ -0,0 +1,493 @@
+//! Contains [Replay], which records every action done on a [Minefield] along
+//! with the time it was done, so that games can be saved and looked at later.
+//!
+//! Replays are saved as text. The first line is the size and the amount of
+//! mines as `<width>x<height>:<mines>` and the topology unless it is the
+//! default one, like in [crate::text_format], followed by `questions` if
+//! question marks were enabled, see [Minefield::set_question_marks]. The
+//! second line is either `seed <seed> <policy>` for generated minefields, or
+//! `mines` followed by the coordinates of every mine as `<x>,<y>`. Every line
+//! after that is a single action, as the milliseconds since the first action,
+//! the action (`reveal`, `flag` or `chord`) and the coordinate:
+//! ```text
+//! 9x9:10
+//! seed 1234 opening
+//! 0 reveal 4,4
+//! 1520 flag 2,3
+//! 2210 chord 3,3
+//! ```
+//!
+//! Replays are played back with [ReplayPlayer].
+
+use std::{
+    fmt::Display,
+    fs,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    history::Action,
+    minefield::{
+        Cell, Coord, GameState, GenerationPolicy, Matrix, Minefield, MinefieldError, Topology,
+    },
+};
+
+/// Describes how the mines of the replayed minefield are placed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayBoard<const W: usize, const H: usize> {
+    /// The minefield was generated from the seed with the policy, see
+    /// [Minefield::generate_sized].
+    Seed(u64, GenerationPolicy),
+    /// The mines of the minefield were at these coordinates.
+    Mines(Vec<Coord<W, H>>),
+}
+
+/// An action along with the time since the first action of the game.
+pub type TimedAction<const W: usize, const H: usize> = (Duration, Action<W, H>);
+
+/// Recording of a single game on a [Minefield], that can be saved into a file
+/// and loaded later, see [crate::replay].
+#[derive(Clone, Debug)]
+pub struct Replay<const W: usize, const H: usize> {
+    /// Width of the minefield
+    pub width: u16,
+    /// Height of the minefield
+    pub height: u16,
+    /// How many mines are in the minefield
+    pub mines: u16,
+    /// How the mines are placed
+    pub board: ReplayBoard<W, H>,
+    /// The topology of the minefield
+    pub topology: Topology,
+    /// Whether flagging cycles through question marks
+    pub question_marks: bool,
+    /// Every recorded action
+    pub actions: Vec<TimedAction<W, H>>,
+    /// When the first action was recorded
+    started: Option<Instant>,
+}
+
+impl<const W: usize, const H: usize> Replay<W, H> {
+    /// Start a new replay for the given minefield, which should not have any
+    /// actions done on it yet. Minefields without a seed are recorded by the
+    /// placement of their mines.
+    pub fn new(minefield: &Minefield<W, H>) -> Self {
+        let board = match minefield.seed() {
+            Some(seed) => ReplayBoard::Seed(seed, minefield.policy()),
+            None => {
+                let mut mines = Vec::new();
+                for y in 0..minefield.height() {
+                    for x in 0..minefield.width() {
+                        if minefield.is_mine(Coord(x, y)) {
+                            mines.push(Coord(x, y));
+                        }
+                    }
+                }
+                ReplayBoard::Mines(mines)
+            }
+        };
+        Replay {
+            width: minefield.width(),
+            height: minefield.height(),
+            mines: minefield.mines,
+            board,
+            topology: minefield.topology(),
+            question_marks: minefield.question_marks(),
+            actions: Vec::new(),
+            started: None,
+        }
+    }
+
+    /// Record an action, timestamped relative to the first recorded action.
+    pub fn record(&mut self, action: Action<W, H>) {
+        let now = Instant::now();
+        let started = *self.started.get_or_insert(now);
+        self.actions.push((now - started, action));
+    }
+
+    /// Forget the latest recorded action, used when an action is undone.
+    pub fn forget_latest(&mut self) -> Option<TimedAction<W, H>> {
+        self.actions.pop()
+    }
+
+    /// Return the minefield as it was before the first action.
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if the size of the replay is zero or
+    ///   larger than the minefield can hold.
+    /// - [MinefieldError::TooManyMines] if the replay has too many mines.
+    /// - [MinefieldError::InvalidCoordinate] if a mine is outside of the
+    ///   minefield.
+    pub fn minefield(&self) -> Result<Minefield<W, H>, MinefieldError> {
+        let mut minefield = match &self.board {
+            ReplayBoard::Seed(seed, policy) => {
+                Minefield::generate_sized(self.width, self.height, self.mines, *seed, *policy)?
+            }
+            ReplayBoard::Mines(coords) => {
+                if self.width == 0
+                    || self.height == 0
+                    || self.width as usize > W
+                    || self.height as usize > H
+                {
+                    return Err(MinefieldError::InvalidSize);
+                }
+                let mut mines = Matrix([[false; W]; H]);
+                let mut field = Matrix([[Cell::Empty; W]; H]);
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        field.set(Coord(x, y), Cell::Hidden);
+                    }
+                }
+                for coord in coords {
+                    if coord.0 >= self.width || coord.1 >= self.height {
+                        return Err(MinefieldError::InvalidCoordinate);
+                    }
+                    mines.set(*coord, true);
+                }
+                Minefield::from_parts(self.width, self.height, mines, field)
+            }
+        };
+        minefield.set_topology(self.topology);
+        minefield.set_question_marks(self.question_marks);
+        Ok(minefield)
+    }
+
+    /// Return a file name for the replay of a game that ended in the given
+    /// state, which is unique for every millisecond.
+    pub fn file_name(&self, game_state: GameState) -> String {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let board = match self.board {
+            ReplayBoard::Seed(seed, _) => format!("seed-{}", seed),
+            ReplayBoard::Mines(_) => String::from("board"),
+        };
+        let result = match game_state {
+            GameState::Victory => "victory",
+            GameState::GameOver => "loss",
+            GameState::Pending => "unfinished",
+        };
+        format!("{}-{}-{}.replay", time, board, result)
+    }
+
+    /// Load a replay from a file, see [crate::replay].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be read.
+    /// - [MinefieldError::InvalidFormat] if the file is not a valid replay.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MinefieldError> {
+        fs::read_to_string(path)
+            .map_err(|e| MinefieldError::Io(e.kind()))?
+            .parse()
+    }
+
+    /// Save the replay into a file, see [crate::replay].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MinefieldError> {
+        fs::write(path, self.to_string()).map_err(|e| MinefieldError::Io(e.kind()))
+    }
+}
+
+/// Plays a [Replay] back on its minefield at any speed, one action at a time
+/// or by seeking to any point of time.
+#[derive(Clone, Debug)]
+pub struct ReplayPlayer<const W: usize, const H: usize> {
+    replay: Replay<W, H>,
+    /// The minefield before the first action, used when seeking backwards.
+    initial: Minefield<W, H>,
+    minefield: Minefield<W, H>,
+    /// How many actions have been played so far.
+    position: usize,
+    time: Duration,
+    /// How fast the replay is played, 1.0 being real speed.
+    pub speed: f32,
+    /// Whether the playback is paused, see [ReplayPlayer::advance].
+    pub paused: bool,
+}
+
+impl<const W: usize, const H: usize> ReplayPlayer<W, H> {
+    /// Start playing the replay from the beginning at real speed.
+    ///
+    /// # Errors
+    /// Same as [Replay::minefield].
+    pub fn new(replay: Replay<W, H>) -> Result<Self, MinefieldError> {
+        let initial = replay.minefield()?;
+        Ok(ReplayPlayer {
+            replay,
+            minefield: initial.clone(),
+            initial,
+            position: 0,
+            time: Duration::ZERO,
+            speed: 1.,
+            paused: false,
+        })
+    }
+
+    /// Return the replay being played.
+    #[inline]
+    pub fn replay(&self) -> &Replay<W, H> {
+        &self.replay
+    }
+
+    /// Return the minefield as it is at the current point of the replay.
+    #[inline]
+    pub fn minefield(&self) -> &Minefield<W, H> {
+        &self.minefield
+    }
+
+    /// Return how many actions have been played so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Return the current point of time in the replay.
+    #[inline]
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Return the length of the replay, which is the time of the last action.
+    pub fn duration(&self) -> Duration {
+        self.replay
+            .actions
+            .last()
+            .map(|(t, _)| *t)
+            .unwrap_or_default()
+    }
+
+    /// Return the latest played action, if any.
+    pub fn latest(&self) -> Option<Action<W, H>> {
+        self.position
+            .checked_sub(1)
+            .map(|i| self.replay.actions[i].1)
+    }
+
+    /// Advance the playback by the given amount of real time, scaled by
+    /// [ReplayPlayer::speed], and play every action up to that point. Does
+    /// nothing if the playback is paused.
+    pub fn advance(&mut self, delta: Duration) {
+        if !self.paused {
+            let time = (self.time + delta.mul_f32(self.speed.max(0.))).min(self.duration());
+            self.play_until(time);
+        }
+    }
+
+    /// Play the next action and move the time to it. Returns false if there
+    /// are no more actions.
+    pub fn step_forward(&mut self) -> bool {
+        let played = self.play_next();
+        if played {
+            self.time = self.latest_time();
+        }
+        played
+    }
+
+    /// Take back the latest action and move the time to the action before it.
+    /// Returns false if no actions have been played.
+    pub fn step_backward(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.seek_position(self.position - 1);
+        true
+    }
+
+    /// Move to the given point of time in the replay, playing every action up
+    /// to it.
+    pub fn seek(&mut self, time: Duration) {
+        let time = time.min(self.duration());
+        if time < self.time {
+            self.seek_position(0);
+        }
+        self.play_until(time);
+    }
+
+    /// Play every action with a time at most the given time.
+    fn play_until(&mut self, time: Duration) {
+        while matches!(self.replay.actions.get(self.position), Some((t, _)) if *t <= time) {
+            self.play_next();
+        }
+        self.time = time;
+    }
+
+    /// Play the next action, returns false if there are no more actions.
+    fn play_next(&mut self) -> bool {
+        let Some((_, action)) = self.replay.actions.get(self.position) else {
+            return false;
+        };
+        // Replays only contain actions that succeeded when recorded, so errors
+        // can only come from an edited replay file and are skipped.
+        let _ = match *action {
+            Action::Reveal(coord) => self.minefield.reveal(coord),
+            Action::Flag(coord) => self.minefield.flag(coord),
+            Action::Chord(coord) => self.minefield.chord(coord),
+        };
+        self.position += 1;
+        true
+    }
+
+    /// Start over from the initial minefield and play the given amount of
+    /// actions.
+    fn seek_position(&mut self, position: usize) {
+        self.minefield = self.initial.clone();
+        self.position = 0;
+        while self.position < position && self.play_next() {}
+        self.time = self.latest_time();
+    }
+
+    /// Return the time of the latest played action, zero if none.
+    fn latest_time(&self) -> Duration {
+        self.position
+            .checked_sub(1)
+            .map(|i| self.replay.actions[i].0)
+            .unwrap_or_default()
+    }
+}
+
+impl<const W: usize, const H: usize> Display for Replay<W, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}:{}", self.width, self.height, self.mines)?;
+        if self.topology != Topology::Moore {
+            write!(f, " {}", self.topology)?;
+        }
+        if self.question_marks {
+            write!(f, " questions")?;
+        }
+        writeln!(f)?;
+        match &self.board {
+            ReplayBoard::Seed(seed, policy) => writeln!(f, "seed {} {}", seed, policy)?,
+            ReplayBoard::Mines(mines) => {
+                write!(f, "mines")?;
+                for coord in mines {
+                    write!(f, " {},{}", coord.0, coord.1)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        for (time, action) in &self.actions {
+            let (name, coord) = match action {
+                Action::Reveal(coord) => ("reveal", coord),
+                Action::Flag(coord) => ("flag", coord),
+                Action::Chord(coord) => ("chord", coord),
+            };
+            writeln!(f, "{} {} {},{}", time.as_millis(), name, coord.0, coord.1)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const W: usize, const H: usize> FromStr for Replay<W, H> {
+    type Err = MinefieldError;
+
+    /// Parse a replay from text, see [crate::replay].
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidFormat] if the text is not a valid replay.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let (line, header) = lines
+            .next()
+            .ok_or(MinefieldError::InvalidFormat { line: 1 })?;
+        let error = || MinefieldError::InvalidFormat { line };
+        let mut words = header.split_whitespace();
+        let header = words.next().ok_or_else(error)?;
+        let (mut topology, mut question_marks) = (Topology::default(), false);
+        let mut word = words.next();
+        if let Some(text) = word.filter(|text| *text != "questions") {
+            topology = text.parse().map_err(|_| error())?;
+            word = words.next();
+        }
+        if word == Some("questions") {
+            question_marks = true;
+            word = words.next();
+        }
+        if word.is_some() {
+            return Err(error());
+        }
+        let (size, mines) = header.split_once(':').ok_or_else(error)?;
+        let (width, height) = size.split_once('x').ok_or_else(error)?;
+        let (width, height, mines) = match (width.parse(), height.parse(), mines.parse()) {
+            (Ok(width), Ok(height), Ok(mines)) => (width, height, mines),
+            _ => return Err(error()),
+        };
+
+        let (line, board) = lines
+            .next()
+            .ok_or(MinefieldError::InvalidFormat { line: line + 1 })?;
+        let error = || MinefieldError::InvalidFormat { line };
+        let mut words = board.split_whitespace();
+        let board = match words.next() {
+            Some("seed") => {
+                let seed = words
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(error)?;
+                let policy = words
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(error)?;
+                ReplayBoard::Seed(seed, policy)
+            }
+            Some("mines") => ReplayBoard::Mines(
+                words
+                    .by_ref()
+                    .map(|word| parse_coord(word).ok_or_else(error))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(error()),
+        };
+        if words.next().is_some() {
+            return Err(error());
+        }
+
+        let mut actions = Vec::new();
+        for (line, text) in lines {
+            let error = || MinefieldError::InvalidFormat { line };
+            let mut words = text.split_whitespace();
+            let time = words
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(error)?;
+            let kind = words.next().ok_or_else(error)?;
+            let coord = words.next().and_then(parse_coord).ok_or_else(error)?;
+            let action = match kind {
+                "reveal" => Action::Reveal(coord),
+                "flag" => Action::Flag(coord),
+                "chord" => Action::Chord(coord),
+                _ => return Err(error()),
+            };
+            if words.next().is_some() {
+                return Err(error());
+            }
+            actions.push((Duration::from_millis(time), action));
+        }
+
+        Ok(Replay {
+            width,
+            height,
+            mines,
+            board,
+            topology,
+            question_marks,
+            actions,
+            started: None,
+        })
+    }
+}
+
+/// Parse a coordinate written as `<x>,<y>`
+fn parse_coord<const W: usize, const H: usize>(text: &str) -> Option<Coord<W, H>> {
+    let (x, y) = text.split_once(',')?;
+    Some(Coord(x.parse().ok()?, y.parse().ok()?))
+}
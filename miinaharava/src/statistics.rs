@@ -0,0 +1,245 @@
+//! Contains [ClickStats], which counts the clicks of a single game, and
+//! [Statistics], which keeps the results of every finished game per
+//! difficulty so they can be saved and loaded.
+//!
+//! Together with the 3BV of the minefield, see [crate::analysis], the clicks
+//! tell how efficiently a game was played. The efficiency (IOE) is the 3BV
+//! divided by the amount of clicks, so a game where every click was necessary
+//! has an efficiency of 1, and chording can even make it larger than 1.
+//!
+//! Statistics are saved as text, with a line for every difficulty: the name of
+//! the difficulty, the amount of games and victories, the total time of the
+//! victories in milliseconds, the total 3BV of the victories, the left clicks,
+//! right clicks, chords and wasted clicks in the victories, and the best time
+//! in milliseconds, or `-` if there are no victories yet:
+//! ```text
+//! easy 12 7 160311 121 133 20 4 9 15040
+//! expert 3 0 0 0 0 0 0 0 -
+//! ```
+
+use std::{collections::BTreeMap, fmt::Display, fs, path::Path, str::FromStr, time::Duration};
+
+use crate::{
+    events::MinefieldEvent,
+    history::Action,
+    minefield::{GameState, MinefieldError},
+};
+
+/// The clicks done in a game, see [crate::statistics].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClickStats {
+    /// How many tiles have been revealed
+    pub left: u32,
+    /// How many tiles have been flagged or unflagged
+    pub right: u32,
+    /// How many tiles have been chorded
+    pub chords: u32,
+    /// How many of the clicks did not change anything, or removed a flag
+    /// again, which wasted the click that placed it.
+    pub wasted: u32,
+}
+
+impl ClickStats {
+    /// Record a single click, given the events of the minefield that the
+    /// action caused, see [crate::events].
+    pub fn record<const W: usize, const H: usize>(
+        &mut self,
+        action: Action<W, H>,
+        events: &[MinefieldEvent<W, H>],
+    ) {
+        match action {
+            Action::Reveal(_) => self.left += 1,
+            Action::Flag(_) => self.right += 1,
+            Action::Chord(_) => self.chords += 1,
+        }
+        let unflagged = matches!(action, Action::Flag(coord)
+            if events.contains(&MinefieldEvent::FlagRemoved(coord)));
+        if events.is_empty() || unflagged {
+            self.wasted += 1;
+        }
+    }
+
+    /// The total amount of clicks.
+    pub fn total(&self) -> u32 {
+        self.left + self.right + self.chords
+    }
+
+    /// The efficiency (IOE) of the clicks for a minefield with the given 3BV,
+    /// see [crate::statistics].
+    pub fn efficiency(&self, bbbv: u32) -> f32 {
+        bbbv as f32 / self.total().max(1) as f32
+    }
+
+    /// How many clicks were done per second in the given time.
+    pub fn per_second(&self, time: Duration) -> f32 {
+        self.total() as f32 / time.as_secs_f32().max(f32::EPSILON)
+    }
+
+    /// Add the clicks of another game.
+    pub fn combine(&mut self, other: &ClickStats) -> &mut Self {
+        self.left += other.left;
+        self.right += other.right;
+        self.chords += other.chords;
+        self.wasted += other.wasted;
+        self
+    }
+}
+
+/// The results of every finished game on a single difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DifficultyStats {
+    /// How many games have been finished
+    pub games: u32,
+    /// How many of the games were won
+    pub victories: u32,
+    /// The total time of all victories
+    pub time: Duration,
+    /// The total 3BV of all victories
+    pub bbbv: u32,
+    /// The total clicks of all victories
+    pub clicks: ClickStats,
+    /// The fastest victory, if any
+    pub best_time: Option<Duration>,
+}
+
+impl DifficultyStats {
+    /// The average efficiency (IOE) of the victories, see
+    /// [crate::statistics].
+    pub fn efficiency(&self) -> f32 {
+        self.clicks.efficiency(self.bbbv)
+    }
+
+    /// The average 3BV per second of the victories.
+    pub fn bbbv_per_second(&self) -> f32 {
+        self.bbbv as f32 / self.time.as_secs_f32().max(f32::EPSILON)
+    }
+}
+
+/// The results of finished games per difficulty, see [crate::statistics].
+/// Difficulties are named freely, but the names can not contain whitespace.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Statistics(pub BTreeMap<String, DifficultyStats>);
+
+impl Statistics {
+    /// Record a finished game on the given difficulty. Only victories are
+    /// counted in the time, 3BV and clicks. Unfinished games are ignored.
+    pub fn record(
+        &mut self,
+        difficulty: &str,
+        game_state: GameState,
+        time: Duration,
+        bbbv: u32,
+        clicks: ClickStats,
+    ) -> DifficultyStats {
+        let stats = self.0.entry(difficulty.to_string()).or_default();
+        match game_state {
+            GameState::Victory => {
+                stats.games += 1;
+                stats.victories += 1;
+                stats.time += time;
+                stats.bbbv += bbbv;
+                stats.clicks.combine(&clicks);
+                stats.best_time = Some(stats.best_time.map_or(time, |best| best.min(time)));
+            }
+            GameState::GameOver => stats.games += 1,
+            GameState::Pending => {}
+        }
+        *stats
+    }
+
+    /// Load statistics from a file, see [crate::statistics].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be read.
+    /// - [MinefieldError::InvalidFormat] if the file is not valid statistics.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, MinefieldError> {
+        fs::read_to_string(path)
+            .map_err(|e| MinefieldError::Io(e.kind()))?
+            .parse()
+    }
+
+    /// Save the statistics into a file, see [crate::statistics].
+    ///
+    /// # Errors
+    /// - [MinefieldError::Io] if the file could not be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), MinefieldError> {
+        fs::write(path, self.to_string()).map_err(|e| MinefieldError::Io(e.kind()))
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (difficulty, stats) in &self.0 {
+            let clicks = stats.clicks;
+            write!(
+                f,
+                "{} {} {} {} {} {} {} {} {} ",
+                difficulty,
+                stats.games,
+                stats.victories,
+                stats.time.as_millis(),
+                stats.bbbv,
+                clicks.left,
+                clicks.right,
+                clicks.chords,
+                clicks.wasted
+            )?;
+            match stats.best_time {
+                Some(best) => writeln!(f, "{}", best.as_millis())?,
+                None => writeln!(f, "-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Statistics {
+    type Err = MinefieldError;
+
+    /// Parse statistics from text, see [crate::statistics].
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidFormat] if the text is not valid statistics.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut statistics = Statistics::default();
+        for (line, text) in text.lines().enumerate() {
+            let error = || MinefieldError::InvalidFormat { line: line + 1 };
+            let mut words = text.split_whitespace();
+            let Some(difficulty) = words.next() else {
+                continue;
+            };
+            let mut numbers = [0; 8];
+            for number in &mut numbers {
+                *number = words
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(error)?;
+            }
+            let best_time = match words.next() {
+                Some("-") => None,
+                Some(best) => Some(Duration::from_millis(best.parse().map_err(|_| error())?)),
+                None => return Err(error()),
+            };
+            if words.next().is_some() {
+                return Err(error());
+            }
+
+            let [games, victories, time, bbbv, left, right, chords, wasted] = numbers;
+            let stats = DifficultyStats {
+                games: games as u32,
+                victories: victories as u32,
+                time: Duration::from_millis(time),
+                bbbv: bbbv as u32,
+                clicks: ClickStats {
+                    left: left as u32,
+                    right: right as u32,
+                    chords: chords as u32,
+                    wasted: wasted as u32,
+                },
+                best_time,
+            };
+            statistics.0.insert(difficulty.to_string(), stats);
+        }
+        Ok(statistics)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    analysis::Metrics,
+    minefield::{GenerationPolicy, Minefield, MinefieldError},
+};
+
+#[test]
+fn test_single_opening() {
+    let minefield: Minefield<5, 1> = "5x1:1\n*....\n".parse().unwrap();
+    let metrics = minefield.metrics().unwrap();
+    assert_eq!(
+        metrics,
+        Metrics {
+            bbbv: 1,
+            openings: 1,
+            isolated: 0,
+            islands: 0,
+            zini: 1,
+        }
+    );
+    assert_eq!(minefield.bbbv(), Ok(1));
+}
+
+#[test]
+fn test_no_openings() {
+    let minefield: Minefield<3, 3> = "3x3:1\n...\n.*.\n...\n".parse().unwrap();
+    let metrics = minefield.metrics().unwrap();
+    assert_eq!(
+        metrics,
+        Metrics {
+            bbbv: 8,
+            openings: 0,
+            isolated: 8,
+            islands: 1,
+            zini: 5,
+        }
+    );
+}
+
+#[test]
+fn test_islands() {
+    let minefield: Minefield<7, 1> = "7x1:2\n.*...*.\n".parse().unwrap();
+    let metrics = minefield.metrics().unwrap();
+    assert_eq!(metrics.openings, 1);
+    assert_eq!(metrics.isolated, 2);
+    assert_eq!(metrics.islands, 2);
+    assert_eq!(metrics.bbbv, 3);
+}
+
+#[test]
+fn test_mines_not_placed() {
+    let minefield =
+        Minefield::<9, 9>::generate_with_policy(10, 0, GenerationPolicy::OpeningFirstClick)
+            .unwrap();
+    assert_eq!(minefield.metrics(), Err(MinefieldError::MinesNotPlaced));
+    assert_eq!(minefield.bbbv(), Err(MinefieldError::MinesNotPlaced));
+}
+
+#[test]
+fn test_generated_metrics() {
+    for seed in 0..20 {
+        let minefield =
+            Minefield::<30, 16>::generate_with_policy(99, seed, GenerationPolicy::Random).unwrap();
+        let metrics = minefield.metrics().unwrap();
+        assert_eq!(minefield.bbbv(), Ok(metrics.bbbv));
+        assert_eq!(metrics.bbbv, metrics.openings + metrics.isolated);
+        assert!(metrics.islands <= metrics.isolated);
+        assert!(metrics.zini <= metrics.bbbv);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    events::MinefieldEvent,
+    history::History,
+    minefield::{Cell, Coord, Minefield},
+};
+
+use super::history::MINES;
+
+#[test]
+fn test_flag_events() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    minefield.set_question_marks(true);
+    let subscription = minefield.subscribe();
+
+    assert_eq!(
+        minefield.flag(Coord(3, 1)),
+        Ok(vec![(Coord(3, 1), Cell::Flag)])
+    );
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.flag(Coord(3, 1)).unwrap();
+    assert_eq!(
+        minefield.poll(&subscription),
+        &[
+            MinefieldEvent::FlagPlaced(Coord(3, 1)),
+            MinefieldEvent::FlagRemoved(Coord(3, 1)),
+            MinefieldEvent::QuestionPlaced(Coord(3, 1)),
+            MinefieldEvent::QuestionRemoved(Coord(3, 1)),
+        ]
+    );
+    assert_eq!(minefield.field.get(Coord(3, 1)), Cell::Hidden);
+
+    // Flagging a revealed tile changes nothing
+    minefield.reveal(Coord(3, 0)).unwrap();
+    minefield.poll(&subscription);
+    assert_eq!(minefield.flag(Coord(3, 0)), Ok(Vec::new()));
+    assert!(minefield.poll(&subscription).is_empty());
+}
+
+#[test]
+fn test_reveal_events() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let subscription = minefield.subscribe();
+
+    let reveals = minefield.reveal(Coord(0, 0)).unwrap();
+    let events = minefield.poll(&subscription);
+    assert_eq!(events.len(), reveals.len());
+    for ((coord, cell), event) in reveals.iter().zip(events) {
+        assert_eq!(*event, MinefieldEvent::Revealed(*coord, *cell));
+    }
+
+    minefield.reveal(Coord(3, 1)).unwrap();
+    assert_eq!(
+        minefield.poll(&subscription),
+        &[
+            MinefieldEvent::MineExploded(Coord(3, 1)),
+            MinefieldEvent::GameLost
+        ]
+    );
+}
+
+#[test]
+fn test_victory_event() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let subscription = minefield.subscribe();
+    for (y, row) in MINES.iter().enumerate() {
+        for (x, item) in row.iter().enumerate() {
+            if !*item {
+                minefield.reveal(Coord(x as u16, y as u16)).ok();
+            }
+        }
+    }
+    let events = minefield.poll(&subscription);
+    assert_eq!(events.last(), Some(&MinefieldEvent::GameWon));
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| **e == MinefieldEvent::GameWon)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_subscriptions_are_independent() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    minefield.flag(Coord(4, 1)).unwrap();
+    let first = minefield.subscribe();
+    minefield.flag(Coord(3, 1)).unwrap();
+    let second = minefield.subscribe();
+    minefield.flag(Coord(4, 4)).unwrap();
+
+    assert_eq!(
+        minefield.poll(&first),
+        &[
+            MinefieldEvent::FlagPlaced(Coord(3, 1)),
+            MinefieldEvent::FlagPlaced(Coord(4, 4))
+        ]
+    );
+    assert_eq!(
+        minefield.poll(&second),
+        &[MinefieldEvent::FlagPlaced(Coord(4, 4))]
+    );
+}
+
+#[test]
+fn test_undo_events() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let subscription = minefield.subscribe();
+    let mut history = History::new(minefield);
+    history.reveal(Coord(3, 1)).unwrap();
+    history.undo().unwrap();
+
+    let events = history.poll(&subscription);
+    assert_eq!(
+        events,
+        &[
+            MinefieldEvent::MineExploded(Coord(3, 1)),
+            MinefieldEvent::GameLost,
+            MinefieldEvent::Restored(Coord(3, 1), Cell::Hidden),
+        ]
+    );
+}
+
+#[test]
+fn test_read_events_are_dropped() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let first = minefield.subscribe();
+    let second = minefield.subscribe();
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.flag(Coord(4, 1)).unwrap();
+
+    assert_eq!(minefield.poll(&first).len(), 2);
+    minefield.poll(&first);
+    assert_eq!(minefield.events.as_ref().unwrap().events.len(), 2);
+
+    assert_eq!(minefield.poll(&second).len(), 2);
+    minefield.flag(Coord(4, 4)).unwrap();
+    assert_eq!(
+        minefield.poll(&second),
+        &[MinefieldEvent::FlagPlaced(Coord(4, 4))]
+    );
+    assert_eq!(minefield.events.as_ref().unwrap().events.len(), 1);
+
+    minefield.unsubscribe(second);
+    assert_eq!(
+        minefield.poll(&first),
+        &[MinefieldEvent::FlagPlaced(Coord(4, 4))]
+    );
+    minefield.unsubscribe(first);
+    assert!(minefield.events.is_none());
+}
+
+#[test]
+fn test_events_are_not_cloned_or_compared() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let subscription = minefield.subscribe();
+    minefield.flag(Coord(3, 1)).unwrap();
+
+    let mut clone = minefield.clone();
+    assert!(clone.events.is_none());
+    assert!(clone.poll(&subscription).is_empty());
+    assert_eq!(clone, minefield);
+    assert_eq!(minefield.poll(&subscription).len(), 1);
+}
+
+#[test]
+fn test_subscriptions_of_other_minefields() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    let mut other = Minefield::<5, 5>::with_mines(MINES);
+    let subscription = minefield.subscribe();
+    let other_subscription = other.subscribe();
+    minefield.flag(Coord(3, 1)).unwrap();
+
+    assert!(minefield.poll(&other_subscription).is_empty());
+    minefield.unsubscribe(other_subscription);
+    assert_eq!(
+        minefield.poll(&subscription),
+        &[MinefieldEvent::FlagPlaced(Coord(3, 1))]
+    );
+}
@@ -0,0 +1,159 @@
+use crate::{
+    history::{Action, History},
+    minefield::{Cell, Coord, GameState, Matrix, Minefield, MinefieldError},
+};
+
+pub const MINES: Matrix<bool, 5, 5> = Matrix([
+    [false, false, false, false, false],
+    [false, false, false, true, true],
+    [false, false, false, false, false],
+    [false, false, false, false, true],
+    [false, false, false, false, true],
+]);
+
+#[test]
+fn test_undo_and_redo_reveal() {
+    let minefield = Minefield::<5, 5>::with_mines(MINES);
+    let mut history = History::new(minefield.clone());
+
+    let reveals = history.reveal(Coord(0, 0)).unwrap();
+    let revealed = history.minefield().clone();
+    assert_eq!(history.steps().len(), 1);
+
+    let step = history.undo().unwrap();
+    assert_eq!(step.action, Action::Reveal(Coord(0, 0)));
+    assert_eq!(step.reveals, reveals);
+    assert_eq!(history.minefield().field, minefield.field);
+    assert!(history.undo().is_none());
+
+    history.redo().unwrap();
+    assert_eq!(history.minefield(), &revealed);
+    assert!(history.redo().is_none());
+}
+
+#[test]
+fn test_undo_losing_reveal() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+    history.reveal(Coord(0, 0)).unwrap();
+    history.reveal(Coord(3, 1)).unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::GameOver);
+
+    history.undo().unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::Pending);
+    assert_eq!(history.minefield().field.get(Coord(3, 1)), Cell::Hidden);
+
+    // The game can be continued after undoing
+    history.flag(Coord(3, 1)).unwrap();
+    assert_eq!(history.minefield().field.get(Coord(3, 1)), Cell::Flag);
+
+    // Redo is no longer possible after a new action
+    assert!(history.redo().is_none());
+}
+
+#[test]
+fn test_victory_after_undoing_loss() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+    history.reveal(Coord(3, 1)).unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::GameOver);
+    history.undo().unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::Pending);
+
+    for (y, row) in MINES.iter().enumerate() {
+        for (x, item) in row.iter().enumerate() {
+            if !*item {
+                history.reveal(Coord(x as u16, y as u16)).ok();
+            }
+        }
+    }
+    assert_eq!(history.minefield().game_state(), GameState::Victory);
+}
+
+#[test]
+fn test_undo_victory() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+    for (y, row) in MINES.iter().enumerate() {
+        for (x, item) in row.iter().enumerate() {
+            if !*item {
+                history.reveal(Coord(x as u16, y as u16)).ok();
+            }
+        }
+    }
+    assert_eq!(history.minefield().game_state(), GameState::Victory);
+    history.undo().unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::Pending);
+    history.redo().unwrap();
+    assert_eq!(history.minefield().game_state(), GameState::Victory);
+}
+
+#[test]
+fn test_undo_restores_flags() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+
+    // Flag a tile that is then revealed by the automatic recursive reveal
+    history.flag(Coord(1, 1)).unwrap();
+    history.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Empty);
+
+    history.undo().unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Flag);
+    history.undo().unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Hidden);
+    assert!(history.steps().is_empty());
+}
+
+#[test]
+fn test_undo_restores_question_marks() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    minefield.set_question_marks(true);
+    let mut history = History::new(minefield);
+
+    history.flag(Coord(1, 1)).unwrap();
+    history.flag(Coord(1, 1)).unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Question);
+    history.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Empty);
+
+    history.undo().unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Question);
+    history.undo().unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Flag);
+    history.redo().unwrap();
+    history.redo().unwrap();
+    assert_eq!(history.minefield().field.get(Coord(1, 1)), Cell::Empty);
+}
+
+#[test]
+fn test_undo_chord() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+    history.reveal(Coord(0, 0)).unwrap();
+    history.flag(Coord(3, 1)).unwrap();
+    let before = history.minefield().clone();
+
+    let reveals = history.chord(Coord(2, 1)).unwrap();
+    assert_eq!(reveals, vec![(Coord(3, 0), Cell::Label(2))]);
+    assert_eq!(
+        history.steps().last().unwrap().action,
+        Action::Chord(Coord(2, 1))
+    );
+
+    history.undo().unwrap();
+    assert_eq!(history.minefield(), &before);
+}
+
+#[test]
+fn test_only_changes_are_recorded() {
+    let mut history = History::new(Minefield::<5, 5>::with_mines(MINES));
+    history.reveal(Coord(0, 0)).unwrap();
+
+    // Revealing or flagging an already revealed tile does nothing
+    history.reveal(Coord(0, 0)).unwrap();
+    history.flag(Coord(1, 1)).unwrap();
+    assert_eq!(history.steps().len(), 1);
+
+    // Errors are not recorded either
+    assert_eq!(
+        history.reveal(Coord(5, 5)),
+        Err(MinefieldError::InvalidCoordinate)
+    );
+    assert_eq!(history.steps().len(), 1);
+}
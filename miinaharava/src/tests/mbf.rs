@@ -0,0 +1,97 @@
+use std::io::ErrorKind;
+
+use crate::minefield::{Cell, Coord, GenerationPolicy, Minefield, MinefieldError};
+
+/// A 4x3 board with mines at (1, 0), (3, 1) and (0, 2)
+const BOARD: [u8; 10] = [4, 3, 0, 3, 1, 0, 3, 1, 0, 2];
+
+#[test]
+fn test_load_mbf() {
+    let mut minefield = Minefield::<10, 10>::from_mbf(&BOARD).unwrap();
+    assert_eq!((minefield.width(), minefield.height()), (4, 3));
+    assert_eq!(minefield.mines, 3);
+    assert_eq!(minefield.field.get(Coord(3, 2)), Cell::Hidden);
+    assert_eq!(minefield.field.get(Coord(4, 2)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(3, 3)), Cell::Empty);
+
+    assert_eq!(
+        minefield.reveal(Coord(1, 1)).unwrap(),
+        vec![(Coord(1, 1), Cell::Label(2))]
+    );
+    assert_eq!(
+        minefield.reveal(Coord(1, 0)).unwrap(),
+        vec![(Coord(1, 0), Cell::Mine)]
+    );
+}
+
+#[test]
+fn test_save_mbf() {
+    let minefield = Minefield::<4, 3>::from_mbf(&BOARD).unwrap();
+    assert_eq!(minefield.to_mbf(), Ok(BOARD.to_vec()));
+
+    for seed in 0..20 {
+        let minefield =
+            Minefield::<30, 16>::generate_sized(30, 16, 99, seed, GenerationPolicy::Random)
+                .unwrap();
+        let bytes = minefield.to_mbf().unwrap();
+        assert_eq!(bytes.len(), 4 + 99 * 2);
+        let loaded = Minefield::<30, 16>::from_mbf(&bytes).unwrap();
+        assert_eq!(loaded.to_mbf(), Ok(bytes));
+    }
+}
+
+#[test]
+fn test_mbf_file() {
+    // The process id keeps test runs at the same time from using the same file
+    let path = std::env::temp_dir().join(format!(
+        "miinaharava_test_mbf_file-{}.mbf",
+        std::process::id()
+    ));
+    let minefield = Minefield::<4, 3>::from_mbf(&BOARD).unwrap();
+    minefield.save_mbf(&path).unwrap();
+    assert_eq!(Minefield::load_mbf(&path), Ok(minefield));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        Minefield::<4, 3>::load_mbf(&path),
+        Err(MinefieldError::Io(ErrorKind::NotFound))
+    );
+}
+
+#[test]
+fn should_fail_on_invalid_mbf() {
+    let cases: [&[u8]; 6] = [
+        &[],
+        &[4, 3, 0],
+        &[4, 3, 0, 3, 1, 0, 3, 1],
+        &[4, 3, 0, 2, 1, 0, 3, 1, 0, 2],
+        &[4, 3, 0, 3, 1, 0, 3, 1, 1, 0],
+        &[4, 3, 0, 3, 1, 0, 3, 1, 4, 2],
+    ];
+    for bytes in cases {
+        assert_eq!(
+            Minefield::<10, 10>::from_mbf(bytes),
+            Err(MinefieldError::InvalidMbf),
+            "{:?}",
+            bytes
+        );
+    }
+    assert_eq!(
+        Minefield::<3, 3>::from_mbf(&BOARD),
+        Err(MinefieldError::InvalidSize)
+    );
+    assert_eq!(
+        Minefield::<10, 10>::from_mbf(&[0, 3, 0, 0]),
+        Err(MinefieldError::InvalidSize)
+    );
+}
+
+#[test]
+fn should_fail_to_save_unplaced_mines() {
+    let minefield =
+        Minefield::<10, 10>::generate_with_policy(10, 0, GenerationPolicy::SafeFirstClick).unwrap();
+    assert_eq!(minefield.to_mbf(), Err(MinefieldError::MinesNotPlaced));
+
+    let minefield = Minefield::<300, 1>::generate(10).unwrap();
+    assert_eq!(minefield.to_mbf(), Err(MinefieldError::InvalidSize));
+}
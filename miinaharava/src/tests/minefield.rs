@@ -1,6 +1,14 @@
-use std::hint::black_box;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+    hint::black_box,
+};
 
-use crate::minefield::{Cell, Coord, GameState, Matrix, Minefield, MinefieldError};
+use super::history::MINES;
+use crate::minefield::{
+    Cell, Coord, CustomMinefield, GameState, GenerationPolicy, Matrix, Minefield, MinefieldError,
+    Topology, CUSTOM_MAX_SIZE,
+};
 
 #[test]
 fn test_generation() {
@@ -22,12 +30,405 @@ fn test_generation() {
     }
 }
 
+#[test]
+fn test_seeded_generation() {
+    for seed in 0..100 {
+        let mut first = Minefield::<10, 10>::generate_with_seed(black_box(10), seed).unwrap();
+        let mut second = Minefield::<10, 10>::generate_with_seed(black_box(10), seed).unwrap();
+
+        assert_eq!(first.seed(), Some(seed));
+        assert_eq!(first.get_mine_indices(), second.get_mine_indices());
+    }
+
+    let mut first = Minefield::<10, 10>::generate_with_seed(10, 1).unwrap();
+    let mut second = Minefield::<10, 10>::generate_with_seed(10, 2).unwrap();
+    assert_ne!(first.get_mine_indices(), second.get_mine_indices());
+}
+
+#[test]
+fn test_seeded_generation_is_stable() {
+    // The same seed must result in the same minefield on every platform and
+    // every version, otherwise old seeds can no longer be replayed.
+    let mut minefield = Minefield::<10, 10>::generate_with_seed(10, 1234).unwrap();
+
+    let mut expected = Matrix([[false; 10]; 10]);
+    for (x, y) in [
+        (1, 1),
+        (4, 1),
+        (5, 1),
+        (1, 3),
+        (7, 3),
+        (9, 3),
+        (6, 5),
+        (8, 5),
+        (0, 9),
+        (5, 9),
+    ] {
+        expected.set(Coord(x, y), true);
+    }
+
+    assert_eq!(*minefield.get_mine_indices(), expected);
+}
+
+#[test]
+fn test_generation_with_rng() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut minefield = Minefield::<10, 10>::generate_with_rng(10, &mut rng).unwrap();
+    let seed = minefield.seed().unwrap();
+
+    let mut regenerated = Minefield::<10, 10>::generate_with_seed(10, seed).unwrap();
+    assert_eq!(minefield.get_mine_indices(), regenerated.get_mine_indices());
+    assert_eq!(
+        Minefield::<10, 10>::with_mines(*minefield.get_mine_indices()).seed(),
+        None
+    );
+}
+
+#[test]
+fn test_safe_first_click_policy() {
+    for seed in 0..200 {
+        let mut minefield =
+            Minefield::<10, 10>::generate_with_policy(99, seed, GenerationPolicy::SafeFirstClick)
+                .unwrap();
+        // Mines are only placed on the first reveal
+        assert!(!minefield.get_mine_indices().iter().flatten().any(|m| *m));
+
+        let coord = Coord::random();
+        minefield.reveal(coord).unwrap();
+        assert_ne!(minefield.field.get(coord), Cell::Mine);
+        assert_ne!(minefield.game_state(), GameState::GameOver);
+        let mine_count = minefield
+            .get_mine_indices()
+            .iter()
+            .flatten()
+            .filter(|m| **m)
+            .count();
+        assert_eq!(mine_count, 99);
+    }
+}
+
+#[test]
+fn test_opening_first_click_policy() {
+    for seed in 0..200 {
+        let mut minefield = Minefield::<10, 10>::generate_with_policy(
+            91,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+
+        let coord = Coord::random();
+        let reveals = minefield.reveal(coord).unwrap();
+        assert_eq!(reveals[0], (coord, Cell::Empty));
+        let mine_count = minefield
+            .get_mine_indices()
+            .iter()
+            .flatten()
+            .filter(|m| **m)
+            .count();
+        assert_eq!(mine_count, 91);
+    }
+}
+
+#[test]
+fn test_classic_relocation_policy() {
+    for seed in 0..200 {
+        let mut minefield = Minefield::<10, 10>::generate_with_policy(
+            10,
+            seed,
+            GenerationPolicy::ClassicRelocation,
+        )
+        .unwrap();
+        let before = *minefield.get_mine_indices();
+        let mine_coord = find_cell(&mut minefield, true).unwrap();
+        let free_coord = find_cell(&mut minefield, false).unwrap();
+
+        minefield.reveal(mine_coord).unwrap();
+        assert_ne!(minefield.game_state(), GameState::GameOver);
+
+        let mut expected = before;
+        expected.set(mine_coord, false);
+        expected.set(free_coord, true);
+        assert_eq!(*minefield.get_mine_indices(), expected);
+
+        // Only the first reveal is ever relocated
+        let mine_coord = find_cell(&mut minefield, true).unwrap();
+        minefield.reveal(mine_coord).unwrap();
+        assert_eq!(minefield.game_state(), GameState::GameOver);
+    }
+}
+
+#[test]
+fn test_policy_is_reproducible() {
+    for policy in [
+        GenerationPolicy::Random,
+        GenerationPolicy::SafeFirstClick,
+        GenerationPolicy::OpeningFirstClick,
+        GenerationPolicy::ClassicRelocation,
+    ] {
+        let mut first = Minefield::<10, 10>::generate_with_policy(20, 5, policy).unwrap();
+        let mut second = Minefield::<10, 10>::generate_with_policy(20, 5, policy).unwrap();
+        first.reveal(Coord(3, 3)).unwrap();
+        second.reveal(Coord(3, 3)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.policy(), policy);
+    }
+}
+
+#[test]
+fn should_fail_on_too_many_mines_for_policy() {
+    assert_eq!(
+        Minefield::<10, 10>::generate_with_policy(100, 0, GenerationPolicy::SafeFirstClick),
+        Err(MinefieldError::TooManyMines)
+    );
+    assert_eq!(
+        Minefield::<10, 10>::generate_with_policy(92, 0, GenerationPolicy::OpeningFirstClick),
+        Err(MinefieldError::TooManyMines)
+    );
+}
+
+#[test]
+fn test_sized_generation() {
+    let mut sized =
+        Minefield::<16, 16>::generate_sized(10, 10, 10, 1234, GenerationPolicy::Random).unwrap();
+    let mut exact = Minefield::<10, 10>::generate_with_seed(10, 1234).unwrap();
+    assert_eq!((sized.width(), sized.height()), (10, 10));
+
+    // Same size and seed should result in the same mines, regardless of the
+    // const-generic size
+    let exact_indices = *exact.get_mine_indices();
+    for (y, row) in sized.get_mine_indices().iter().enumerate() {
+        for (x, item) in row.iter().enumerate() {
+            let expected = x < 10 && y < 10 && exact_indices.0[y][x];
+            assert_eq!(*item, expected);
+        }
+    }
+}
+
+#[test]
+fn test_sized_minefield() {
+    for seed in 0..100 {
+        let mut minefield =
+            Minefield::<10, 10>::generate_sized(6, 4, 3, seed, GenerationPolicy::Random).unwrap();
+        for (y, row) in minefield.field.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let coord = Coord(x as u16, y as u16);
+                assert_eq!(minefield.contains(coord), x < 6 && y < 4);
+                if !minefield.contains(coord) {
+                    assert_eq!(*cell, Cell::Empty);
+                }
+            }
+        }
+        assert!(minefield
+            .neighbours(Coord(5, 3))
+            .iter()
+            .all(|c| minefield.contains(*c)));
+        assert_eq!(
+            minefield.reveal(Coord(6, 0)),
+            Err(MinefieldError::InvalidCoordinate)
+        );
+        assert_eq!(
+            minefield.flag(Coord(0, 4)),
+            Err(MinefieldError::InvalidCoordinate)
+        );
+
+        // Revealing every tile that is not a mine within the minefield should
+        // result in a victory
+        let indices = *minefield.get_mine_indices();
+        for y in 0..4 {
+            for x in 0..6 {
+                if indices.0[y][x] {
+                    continue;
+                }
+                // Game might be victorious ahead of time because of automatic
+                // recursive reveal
+                if let Ok(reveals) = minefield.reveal(Coord(x as u16, y as u16)) {
+                    assert!(reveals.iter().all(|(c, _)| minefield.contains(*c)));
+                }
+            }
+        }
+        assert_eq!(minefield.game_state(), GameState::Victory);
+    }
+}
+
+#[test]
+fn test_large_minefield() {
+    for seed in 0..3 {
+        let mut minefield = Minefield::<100, 100>::generate_with_seed(2000, seed).unwrap();
+        let indices = *minefield.get_mine_indices();
+        assert_eq!(minefield.mines, 2000);
+        assert_eq!(indices.iter().flatten().filter(|m| **m).count(), 2000);
+
+        for (y, row) in indices.iter().enumerate() {
+            for (x, item) in row.iter().enumerate() {
+                if !*item {
+                    // Game might be victorious ahead of time because of
+                    // automatic recursive reveal, just ok() this
+                    minefield.reveal(Coord(x as u16, y as u16)).ok();
+                }
+            }
+        }
+        assert_eq!(minefield.game_state(), GameState::Victory);
+    }
+}
+
+#[test]
+fn test_coord_hash_is_unique() {
+    let mut hashes = HashSet::new();
+    for y in 0..100 {
+        for x in 0..100 {
+            let mut hasher = DefaultHasher::new();
+            Coord::<100, 100>(x, y).hash(&mut hasher);
+            assert!(hashes.insert(hasher.finish()));
+        }
+    }
+}
+
+#[test]
+fn should_fail_on_invalid_size() {
+    use GenerationPolicy::Random;
+    assert_eq!(
+        Minefield::<10, 10>::generate_sized(11, 5, 10, 0, Random),
+        Err(MinefieldError::InvalidSize)
+    );
+    assert_eq!(
+        Minefield::<10, 10>::generate_sized(5, 0, 0, 0, Random),
+        Err(MinefieldError::InvalidSize)
+    );
+    assert_eq!(
+        Minefield::<10, 10>::generate_sized(5, 5, 26, 0, Random),
+        Err(MinefieldError::TooManyMines)
+    );
+}
+
 #[test]
 fn should_fail_on_too_many_mines() {
     assert_eq!(
         Minefield::<10, 10>::generate(101),
         Err(MinefieldError::TooManyMines)
     );
+    assert_eq!(
+        Minefield::<30, 16>::generate(481),
+        Err(MinefieldError::TooManyMines)
+    );
+}
+
+#[test]
+fn test_topology_neighbours() {
+    let neighbours = |topology: Topology, coord: Coord<10, 10>, size: u16| {
+        let mut list = topology.neighbours(coord, size, size).to_vec();
+        list.sort();
+        list
+    };
+
+    assert_eq!(neighbours(Topology::Moore, Coord(0, 0), 10).len(), 3);
+    let mut moore = Coord::<10, 10>(4, 4).neighbours().to_vec();
+    moore.sort();
+    assert_eq!(neighbours(Topology::Moore, Coord(4, 4), 10), moore);
+    assert_eq!(
+        neighbours(Topology::Toroidal, Coord(0, 0), 5),
+        vec![
+            Coord(0, 1),
+            Coord(0, 4),
+            Coord(1, 0),
+            Coord(1, 1),
+            Coord(1, 4),
+            Coord(4, 0),
+            Coord(4, 1),
+            Coord(4, 4),
+        ]
+    );
+    // Wrapping around a small minefield does not produce duplicates or the
+    // tile itself
+    assert_eq!(neighbours(Topology::Toroidal, Coord(0, 0), 2).len(), 3);
+    assert_eq!(neighbours(Topology::Toroidal, Coord(0, 0), 1).len(), 0);
+    assert_eq!(
+        neighbours(Topology::Orthogonal, Coord(0, 3), 10),
+        vec![Coord(0, 2), Coord(0, 4), Coord(1, 3)]
+    );
+    assert_eq!(
+        neighbours(Topology::Knight, Coord(1, 1), 10),
+        vec![Coord(0, 3), Coord(2, 3), Coord(3, 0), Coord(3, 2)]
+    );
+    assert_eq!(neighbours(Topology::Knight, Coord(4, 4), 10).len(), 8);
+    assert_eq!(
+        neighbours(Topology::Hexagonal, Coord(4, 4), 10),
+        vec![
+            Coord(3, 3),
+            Coord(3, 4),
+            Coord(3, 5),
+            Coord(4, 3),
+            Coord(4, 5),
+            Coord(5, 4),
+        ]
+    );
+    assert_eq!(
+        neighbours(Topology::Hexagonal, Coord(4, 5), 10),
+        vec![
+            Coord(3, 5),
+            Coord(4, 4),
+            Coord(4, 6),
+            Coord(5, 4),
+            Coord(5, 5),
+            Coord(5, 6),
+        ]
+    );
+    assert_eq!(neighbours(Topology::Hexagonal, Coord(0, 0), 10).len(), 2);
+    assert_eq!(neighbours(Topology::Hexagonal, Coord(9, 1), 10).len(), 3);
+
+    for topology in [
+        Topology::Moore,
+        Topology::Toroidal,
+        Topology::Orthogonal,
+        Topology::Knight,
+        Topology::Hexagonal,
+    ] {
+        assert_eq!(topology.to_string().parse(), Ok(topology));
+    }
+}
+
+#[test]
+fn test_topology_labels_and_reveals() {
+    let mut mines = Matrix([[false; 5]; 5]);
+    mines.set(Coord(1, 1), true);
+    mines.set(Coord(4, 4), true);
+
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.set_topology(Topology::Toroidal);
+    minefield.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Label(2));
+
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.set_topology(Topology::Orthogonal);
+    // Diagonal mines do not count with the orthogonal topology, so the reveal
+    // is an opening that spreads past both mines
+    minefield.reveal(Coord(2, 2)).unwrap();
+    assert_eq!(minefield.field.get(Coord(2, 2)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(3, 3)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(1, 2)), Cell::Label(1));
+    assert_eq!(minefield.field.get(Coord(4, 3)), Cell::Label(1));
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Hidden);
+
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.set_topology(Topology::Knight);
+    minefield.reveal(Coord(2, 3)).unwrap();
+    assert_eq!(minefield.field.get(Coord(2, 3)), Cell::Label(2));
+    minefield.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(1, 2)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(2, 1)), Cell::Empty);
+
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.set_topology(Topology::Hexagonal);
+    // (0, 0) does not touch the mine diagonally below it on a hexagonal
+    // minefield, while both of its other neighbours do
+    minefield.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(1, 0)), Cell::Label(1));
+    assert_eq!(minefield.field.get(Coord(0, 1)), Cell::Label(1));
+    assert_eq!(minefield.field.get(Coord(1, 2)), Cell::Hidden);
 }
 
 #[test]
@@ -54,7 +455,7 @@ fn test_game_state() {
             if !*item {
                 // Game might be victorious ahead of time because of automatic
                 // recursive reveal, just ok() this
-                minefield.reveal(Coord(x as u8, y as u8)).ok();
+                minefield.reveal(Coord(x as u16, y as u16)).ok();
             }
         }
     }
@@ -86,6 +487,49 @@ fn test_automatic_recursive_reveal() {
     assert_eq!(minefield.field, expected);
 }
 
+#[test]
+fn test_chord() {
+    use Cell::*;
+
+    let mines = Matrix([
+        [false, false, false, false, false],
+        [false, false, false, true, true],
+        [false, false, false, false, false],
+        [false, false, false, false, true],
+        [false, false, false, false, true],
+    ]);
+
+    // Chording an unsatisfied label does nothing
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.reveal(Coord(3, 2)).unwrap();
+    assert_eq!(minefield.chord(Coord(3, 2)).unwrap(), Vec::new());
+
+    // Chording a satisfied label reveals all hidden neighbours
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.flag(Coord(4, 1)).unwrap();
+    minefield.flag(Coord(4, 3)).unwrap();
+    let reveals = minefield.chord(Coord(3, 2)).unwrap();
+    assert!(reveals.contains(&(Coord(2, 1), Label(1))));
+    assert_eq!(minefield.field.get(Coord(4, 2)), Label(3));
+    assert_eq!(minefield.field.get(Coord(2, 3)), Empty);
+    assert_eq!(minefield.field.get(Coord(3, 1)), Flag);
+    assert_eq!(minefield.game_state(), GameState::Pending);
+
+    // Chording with a wrong flag loses the game
+    let mut minefield = Minefield::<5, 5>::with_mines(mines);
+    minefield.reveal(Coord(3, 2)).unwrap();
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.flag(Coord(4, 1)).unwrap();
+    minefield.flag(Coord(4, 2)).unwrap();
+    let reveals = minefield.chord(Coord(3, 2)).unwrap();
+    assert!(reveals.contains(&(Coord(4, 3), Mine)));
+    assert_eq!(minefield.game_state(), GameState::GameOver);
+    assert_eq!(
+        minefield.chord(Coord(3, 2)),
+        Err(MinefieldError::GameHasEnded)
+    );
+}
+
 #[test]
 fn test_flag() {
     let mut minefield = Minefield::<10, 10>::generate(10).unwrap();
@@ -96,7 +540,7 @@ fn test_flag() {
 
     for (y, row) in minefield.field.iter().enumerate() {
         for (x, item) in row.iter().enumerate() {
-            if Coord(x as u8, y as u8) == random_coord {
+            if Coord(x as u16, y as u16) == random_coord {
                 assert_eq!(*item, Cell::Flag);
             } else {
                 assert_eq!(*item, Cell::Hidden);
@@ -125,6 +569,95 @@ fn test_flag() {
     assert_eq!(minefield.field.get(empty_coord), curr_cell);
 }
 
+#[test]
+fn test_flag_counter() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    assert_eq!((minefield.flags(), minefield.remaining_mines()), (0, 4));
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.flag(Coord(4, 1)).unwrap();
+    minefield.flag(Coord(0, 0)).unwrap();
+    assert_eq!((minefield.flags(), minefield.remaining_mines()), (3, 1));
+    minefield.flag(Coord(4, 1)).unwrap();
+    assert_eq!(minefield.flags(), 2);
+
+    // Flags without a mine are removed by revealed openings
+    minefield.reveal(Coord(0, 4)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Empty);
+    assert_eq!((minefield.flags(), minefield.remaining_mines()), (1, 3));
+
+    let parsed: Minefield<3, 1> = "3x1:1\nFf.\n".parse().unwrap();
+    assert_eq!((parsed.flags(), parsed.remaining_mines()), (2, -1));
+}
+
+#[test]
+fn test_final_field_after_loss() {
+    let mut minefield: Minefield<5, 1> = "5x1:2\n*f.1*\n".parse().unwrap();
+    assert_eq!(minefield.final_field(), minefield.field);
+    minefield.reveal(Coord(4, 0)).unwrap();
+    assert_eq!(minefield.game_state(), GameState::GameOver);
+
+    let field = minefield.final_field();
+    assert_eq!(field.get(Coord(0, 0)), Cell::Mine);
+    assert_eq!(field.get(Coord(1, 0)), Cell::WrongFlag);
+    assert_eq!(field.get(Coord(2, 0)), Cell::Hidden);
+    assert_eq!(field.get(Coord(3, 0)), Cell::Label(1));
+    assert_eq!(field.get(Coord(4, 0)), Cell::Exploded);
+    assert_eq!(field.to_string(), "5x1\nXx.1!\n");
+    // The minefield itself is left as it was
+    assert_eq!(minefield.field.get(Coord(4, 0)), Cell::Mine);
+}
+
+#[test]
+fn test_final_field_after_victory() {
+    let mut minefield: Minefield<3, 1> = "3x1:1\n*..\n".parse().unwrap();
+    minefield.reveal(Coord(2, 0)).unwrap();
+    assert_eq!(minefield.game_state(), GameState::Victory);
+    assert_eq!(minefield.final_field().to_string(), "3x1\nF10\n");
+}
+
+#[test]
+fn test_question_marks() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    assert!(!minefield.question_marks());
+    minefield.set_question_marks(true);
+
+    let cycle = [Cell::Flag, Cell::Question, Cell::Hidden, Cell::Flag];
+    for cell in cycle {
+        minefield.flag(Coord(0, 0)).unwrap();
+        assert_eq!(minefield.field.get(Coord(0, 0)), cell);
+    }
+
+    // Without question marks, a question mark is flagged like a hidden tile
+    minefield.flag(Coord(0, 0)).unwrap();
+    minefield.set_question_marks(false);
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Question);
+    minefield.flag(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Flag);
+    minefield.flag(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Hidden);
+
+    // Question marks are revealed and chorded like hidden tiles, and count as
+    // hidden for victory
+    minefield.set_question_marks(true);
+    for coord in [Coord(0, 0), Coord(3, 0), Coord(4, 4)] {
+        minefield.flag(coord).unwrap();
+        minefield.flag(coord).unwrap();
+        assert_eq!(minefield.field.get(coord), Cell::Question);
+    }
+    minefield.reveal(Coord(0, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(3, 0)), Cell::Question);
+    minefield.flag(Coord(3, 1)).unwrap();
+    minefield.chord(Coord(2, 1)).unwrap();
+    assert_eq!(minefield.field.get(Coord(3, 0)), Cell::Label(2));
+    minefield.flag(Coord(4, 1)).unwrap();
+    minefield.flag(Coord(4, 3)).unwrap();
+    minefield.chord(Coord(3, 2)).unwrap();
+    minefield.reveal(Coord(4, 0)).unwrap();
+    assert_eq!(minefield.field.get(Coord(4, 4)), Cell::Question);
+    assert_eq!(minefield.game_state(), GameState::Victory);
+}
+
 #[test]
 fn test_reveal_and_flag_errors() {
     let mut minefield = Minefield::<10, 10>::generate(10).unwrap();
@@ -141,6 +674,11 @@ fn test_reveal_and_flag_errors() {
         Err(MinefieldError::InvalidCoordinate)
     );
 
+    assert_eq!(
+        minefield.chord(out_of_bounds),
+        Err(MinefieldError::InvalidCoordinate)
+    );
+
     // Test clicking a mine
     let mine_coord = find_cell(&mut minefield, true).unwrap();
     minefield.reveal(mine_coord).unwrap();
@@ -162,6 +700,16 @@ fn test_recursive_reveal_wont_panic() {
     minefield.reveal(Coord(5, 5)).unwrap();
 }
 
+#[test]
+fn test_huge_opening_reveal() {
+    let mut mines = Matrix([[false; CUSTOM_MAX_SIZE]; CUSTOM_MAX_SIZE]);
+    mines.set(Coord(0, 0), true);
+    let mut minefield = CustomMinefield::with_mines(mines);
+    let reveals = minefield.reveal(Coord(99, 99)).unwrap();
+    assert_eq!(reveals.len(), CUSTOM_MAX_SIZE * CUSTOM_MAX_SIZE - 1);
+    assert_eq!(minefield.game_state(), GameState::Victory);
+}
+
 fn find_cell<const W: usize, const H: usize>(
     minefield: &mut Minefield<W, H>,
     is_mine: bool,
@@ -170,7 +718,7 @@ fn find_cell<const W: usize, const H: usize>(
     for (y, row) in indices.iter().enumerate() {
         for (x, item) in row.iter().enumerate() {
             if *item == is_mine {
-                return Some(Coord(x as u8, y as u8));
+                return Some(Coord(x as u16, y as u16));
             }
         }
     }
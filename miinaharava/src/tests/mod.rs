@@ -1 +1,8 @@
+mod analysis;
+mod events;
+mod history;
+mod mbf;
 mod minefield;
+mod replay;
+mod statistics;
+mod text_format;
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use crate::{
+    history::{Action, History},
+    minefield::{Cell, Coord, GameState, GenerationPolicy, Minefield, MinefieldError, Topology},
+    replay::{Replay, ReplayBoard, ReplayPlayer},
+};
+
+use super::history::MINES;
+
+const REPLAY: &str = "9x9:10
+seed 1234 opening
+0 reveal 4,4
+1520 flag 2,3
+2210 chord 3,3
+";
+
+/// Play the actions of the replay on its minefield
+fn play<const W: usize, const H: usize>(replay: &Replay<W, H>) -> Minefield<W, H> {
+    let mut history = History::new(replay.minefield().unwrap());
+    for (_, action) in &replay.actions {
+        history.apply(*action).ok();
+    }
+    history.minefield().clone()
+}
+
+#[test]
+fn test_parse_replay() {
+    let replay: Replay<9, 9> = REPLAY.parse().unwrap();
+    assert_eq!((replay.width, replay.height, replay.mines), (9, 9, 10));
+    assert_eq!(
+        replay.board,
+        ReplayBoard::Seed(1234, GenerationPolicy::OpeningFirstClick)
+    );
+    assert_eq!(
+        replay.actions,
+        vec![
+            (Duration::ZERO, Action::Reveal(Coord(4, 4))),
+            (Duration::from_millis(1520), Action::Flag(Coord(2, 3))),
+            (Duration::from_millis(2210), Action::Chord(Coord(3, 3))),
+        ]
+    );
+    assert_eq!(replay.to_string(), REPLAY);
+}
+
+#[test]
+fn test_replay_reproduces_game() {
+    for seed in 0..20 {
+        let minefield = Minefield::<16, 16>::generate_sized(
+            12,
+            10,
+            20,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        let mut replay = Replay::new(&minefield);
+        let mut history = History::new(minefield);
+        let actions = [
+            Action::Reveal(Coord(5, 5)),
+            Action::Flag(Coord(0, 0)),
+            Action::Reveal(Coord(11, 9)),
+            Action::Chord(Coord(5, 5)),
+            Action::Reveal(Coord(0, 9)),
+        ];
+        for action in actions {
+            if history.minefield().game_state() == GameState::Pending
+                && history.apply(action).is_ok()
+            {
+                replay.record(action);
+            }
+        }
+        assert_eq!(replay.actions[0].0, Duration::ZERO);
+
+        let parsed: Replay<16, 16> = replay.to_string().parse().unwrap();
+        assert_eq!(parsed.board, replay.board);
+        assert_eq!(play(&parsed), *history.minefield());
+    }
+}
+
+#[test]
+fn test_replay_with_mines() {
+    let minefield = Minefield::<5, 5>::with_mines(MINES);
+    let mut replay = Replay::new(&minefield);
+    assert_eq!(
+        replay.board,
+        ReplayBoard::Mines(vec![Coord(3, 1), Coord(4, 1), Coord(4, 3), Coord(4, 4)])
+    );
+    replay.record(Action::Reveal(Coord(0, 0)));
+    replay.record(Action::Reveal(Coord(3, 1)));
+    assert_eq!(
+        replay.forget_latest().map(|(_, action)| action),
+        Some(Action::Reveal(Coord(3, 1)))
+    );
+    replay.record(Action::Flag(Coord(3, 1)));
+
+    let parsed: Replay<5, 5> = replay.to_string().parse().unwrap();
+    assert_eq!(parsed.board, replay.board);
+    assert_eq!(parsed.minefield(), Ok(minefield));
+    let played = play(&parsed);
+    assert_eq!(played.game_state(), GameState::Pending);
+    assert_eq!(played.field.get(Coord(3, 1)), Cell::Flag);
+}
+
+#[test]
+fn test_replay_with_question_marks() {
+    let mut minefield = Minefield::<5, 5>::with_mines(MINES);
+    minefield.set_topology(Topology::Toroidal);
+    minefield.set_question_marks(true);
+    let mut replay = Replay::new(&minefield);
+    replay.record(Action::Flag(Coord(0, 0)));
+    replay.record(Action::Flag(Coord(0, 0)));
+
+    let text = replay.to_string();
+    assert!(text.starts_with("5x5:4 torus questions\n"));
+    let parsed: Replay<5, 5> = text.parse().unwrap();
+    assert!(parsed.question_marks);
+    assert_eq!(parsed.topology, Topology::Toroidal);
+    assert_eq!(play(&parsed).field.get(Coord(0, 0)), Cell::Question);
+
+    let parsed: Replay<5, 5> = "5x5:4 questions\nmines 0,0".parse().unwrap();
+    assert!(parsed.question_marks);
+    assert_eq!(parsed.topology, Topology::Moore);
+}
+
+#[test]
+fn test_replay_file() {
+    let replay: Replay<9, 9> = REPLAY.parse().unwrap();
+    let path = std::env::temp_dir().join(replay.file_name(GameState::Victory));
+    assert!(path
+        .to_string_lossy()
+        .ends_with("-seed-1234-victory.replay"));
+    replay.save(&path).unwrap();
+    assert_eq!(Replay::<9, 9>::load(&path).unwrap().to_string(), REPLAY);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        Replay::<9, 9>::load(&path).map(|_| ()),
+        Err(MinefieldError::Io(std::io::ErrorKind::NotFound))
+    );
+}
+
+#[test]
+fn should_fail_on_invalid_replay() {
+    let cases = [
+        ("", 1),
+        ("9x9\nseed 1 random", 1),
+        ("9x9:10", 2),
+        ("9x9:10\nseed 1", 2),
+        ("9x9:10\nseed 1 random extra", 2),
+        ("9x9:10 questions torus\nseed 1 random", 1),
+        ("9x9:10 torus questions extra\nseed 1 random", 1),
+        ("9x9:10\nmines 1,1 2", 2),
+        ("9x9:10\nseed 1 random\n0 reveal", 3),
+        ("9x9:10\nseed 1 random\n0 reveal 1,1\n10 jump 1,1", 4),
+    ];
+    for (text, line) in cases {
+        assert_eq!(
+            text.parse::<Replay<9, 9>>().map(|_| ()),
+            Err(MinefieldError::InvalidFormat { line }),
+            "{:?}",
+            text
+        );
+    }
+    let replay: Replay<9, 9> = "9x9:1\nmines 9,0".parse().unwrap();
+    assert_eq!(replay.minefield(), Err(MinefieldError::InvalidCoordinate));
+    let replay: Replay<5, 5> = REPLAY.parse().unwrap();
+    assert_eq!(replay.minefield(), Err(MinefieldError::InvalidSize));
+}
+
+#[test]
+fn test_replay_player() {
+    let text = "5x5:4
+mines 3,1 4,1 4,3 4,4
+0 reveal 0,0
+1000 flag 3,1
+1000 flag 4,1
+3000 reveal 3,3
+";
+    let replay: Replay<5, 5> = text.parse().unwrap();
+    let mut player = ReplayPlayer::new(replay).unwrap();
+    assert_eq!(player.duration(), Duration::from_secs(3));
+    assert_eq!(player.latest(), None);
+
+    player.advance(Duration::from_millis(500));
+    assert_eq!(player.position(), 1);
+    assert_eq!(player.latest(), Some(Action::Reveal(Coord(0, 0))));
+    assert_eq!(player.minefield().field.get(Coord(0, 0)), Cell::Empty);
+
+    player.speed = 2.;
+    player.advance(Duration::from_millis(500));
+    assert_eq!(player.position(), 3);
+    assert_eq!(player.time(), Duration::from_millis(1500));
+
+    player.paused = true;
+    player.advance(Duration::from_secs(10));
+    assert_eq!(player.position(), 3);
+
+    player.paused = false;
+    player.advance(Duration::from_secs(10));
+    assert_eq!(player.position(), 4);
+    assert_eq!(player.time(), player.duration());
+    assert_eq!(player.minefield().field.get(Coord(3, 3)), Cell::Label(2));
+}
+
+#[test]
+fn test_replay_player_stepping_and_seeking() {
+    let replay: Replay<5, 5> = "5x5:4
+mines 3,1 4,1 4,3 4,4
+0 reveal 0,0
+1000 flag 3,1
+1000 flag 4,1
+3000 reveal 3,1
+"
+    .parse()
+    .unwrap();
+    let mut player = ReplayPlayer::new(replay).unwrap();
+
+    assert!(player.step_forward());
+    assert!(player.step_forward());
+    assert_eq!(player.position(), 2);
+    assert_eq!(player.time(), Duration::from_secs(1));
+    assert_eq!(player.minefield().field.get(Coord(3, 1)), Cell::Flag);
+    assert_eq!(player.minefield().field.get(Coord(4, 1)), Cell::Hidden);
+
+    assert!(player.step_backward());
+    assert_eq!(player.position(), 1);
+    assert_eq!(player.time(), Duration::ZERO);
+    assert_eq!(player.minefield().field.get(Coord(3, 1)), Cell::Hidden);
+
+    player.seek(Duration::from_secs(5));
+    assert_eq!(player.position(), 4);
+    assert_eq!(player.minefield().game_state(), GameState::GameOver);
+
+    player.seek(Duration::from_millis(999));
+    assert_eq!(player.position(), 1);
+    assert_eq!(player.minefield().field.get(Coord(3, 1)), Cell::Hidden);
+
+    player.seek(Duration::ZERO);
+    assert!(player.step_backward());
+    assert!(!player.step_backward());
+    assert_eq!(player.minefield(), &player.replay().minefield().unwrap());
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use crate::{
+    history::{Action, History},
+    minefield::{Coord, GameState, Minefield, MinefieldError},
+    statistics::{ClickStats, DifficultyStats, Statistics},
+};
+
+#[test]
+fn test_click_stats() {
+    let mut minefield: Minefield<5, 1> = "5x1:1\n*....\n".parse().unwrap();
+    let subscription = minefield.subscribe();
+    let mut history = History::new(minefield);
+    let mut clicks = ClickStats::default();
+    for action in [
+        Action::Flag(Coord(0, 0)),
+        Action::Flag(Coord(0, 0)),
+        Action::Reveal(Coord(4, 0)),
+        Action::Reveal(Coord(3, 0)),
+    ] {
+        history.apply(action).ok();
+        clicks.record(action, history.poll(&subscription));
+    }
+
+    assert_eq!(history.minefield().game_state(), GameState::Victory);
+    assert_eq!(
+        clicks,
+        ClickStats {
+            left: 2,
+            right: 2,
+            chords: 0,
+            wasted: 2,
+        }
+    );
+    assert_eq!(clicks.total(), 4);
+    assert_eq!(clicks.efficiency(1), 0.25);
+    assert_eq!(clicks.per_second(Duration::from_secs(2)), 2.);
+}
+
+#[test]
+fn test_record_statistics() {
+    let mut statistics = Statistics::default();
+    let clicks = ClickStats {
+        left: 10,
+        right: 3,
+        chords: 2,
+        wasted: 1,
+    };
+    statistics.record(
+        "easy",
+        GameState::GameOver,
+        Duration::from_secs(3),
+        5,
+        clicks,
+    );
+    statistics.record(
+        "easy",
+        GameState::Victory,
+        Duration::from_secs(20),
+        12,
+        clicks,
+    );
+    statistics.record(
+        "easy",
+        GameState::Pending,
+        Duration::from_secs(1),
+        12,
+        clicks,
+    );
+    let stats = statistics.record(
+        "easy",
+        GameState::Victory,
+        Duration::from_secs(10),
+        8,
+        clicks,
+    );
+
+    assert_eq!(stats.games, 3);
+    assert_eq!(stats.victories, 2);
+    assert_eq!(stats.time, Duration::from_secs(30));
+    assert_eq!(stats.bbbv, 20);
+    assert_eq!(stats.clicks.total(), 30);
+    assert_eq!(stats.best_time, Some(Duration::from_secs(10)));
+    assert_eq!(stats.efficiency(), 20. / 30.);
+    assert_eq!(statistics.0.len(), 1);
+}
+
+#[test]
+fn test_statistics_round_trip() {
+    let text = "easy 12 7 160311 121 133 20 4 9 15040\nexpert 3 0 0 0 0 0 0 0 -\n";
+    let statistics: Statistics = text.parse().unwrap();
+    assert_eq!(
+        statistics.0["expert"],
+        DifficultyStats {
+            games: 3,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        statistics.0["easy"].best_time,
+        Some(Duration::from_millis(15040))
+    );
+    assert_eq!(statistics.to_string(), text);
+}
+
+#[test]
+fn test_invalid_statistics() {
+    for (text, line) in [
+        ("easy 1 1 1 1 1 1 1 1\n", 1),
+        ("easy 1 1 1 1 1 1 1 1 -\nexpert 1 x 1 1 1 1 1 1 -\n", 2),
+        ("easy 1 1 1 1 1 1 1 1 - 1\n", 1),
+    ] {
+        assert_eq!(
+            text.parse::<Statistics>(),
+            Err(MinefieldError::InvalidFormat { line })
+        );
+    }
+    assert_eq!("\n".parse(), Ok(Statistics::default()));
+}
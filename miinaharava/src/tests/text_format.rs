@@ -0,0 +1,159 @@
+use crate::minefield::{
+    Cell, Coord, GameState, GenerationPolicy, Matrix, Minefield, MinefieldError, Topology,
+};
+
+const BOARD: &str = "5x3:3
+001*.
+012f*
+01F..
+";
+
+#[test]
+fn test_parse_minefield() {
+    let minefield: Minefield<5, 3> = BOARD.parse().unwrap();
+    assert_eq!(minefield.mines, 3);
+    assert_eq!(minefield.game_state(), GameState::Pending);
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Empty);
+    assert_eq!(minefield.field.get(Coord(2, 1)), Cell::Label(2));
+    assert_eq!(minefield.field.get(Coord(3, 1)), Cell::Flag);
+    assert_eq!(minefield.field.get(Coord(2, 2)), Cell::Flag);
+    assert_eq!(minefield.field.get(Coord(4, 0)), Cell::Hidden);
+    assert_eq!(minefield.to_text().unwrap(), BOARD);
+}
+
+#[test]
+fn test_minefield_round_trip() {
+    for seed in 0..20 {
+        let mut minefield = Minefield::<30, 16>::generate_with_policy(
+            99,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        minefield.reveal(Coord(15, 8)).unwrap();
+        minefield.flag(Coord(0, 0)).ok();
+        minefield.flag(Coord(29, 15)).ok();
+
+        let parsed: Minefield<30, 16> = minefield.to_text().unwrap().parse().unwrap();
+        assert_eq!(parsed.field, minefield.field);
+        assert_eq!(parsed.mines, minefield.mines);
+        assert_eq!(parsed.game_state(), minefield.game_state());
+        assert_eq!(parsed.to_text().unwrap(), minefield.to_text().unwrap());
+    }
+}
+
+#[test]
+fn test_mines_not_placed() {
+    for policy in [
+        GenerationPolicy::SafeFirstClick,
+        GenerationPolicy::OpeningFirstClick,
+    ] {
+        let mut minefield = Minefield::<5, 5>::generate_with_policy(5, 0, policy).unwrap();
+        assert_eq!(minefield.to_text(), Err(MinefieldError::MinesNotPlaced));
+        minefield.reveal(Coord(2, 2)).unwrap();
+        let parsed: Minefield<5, 5> = minefield.to_text().unwrap().parse().unwrap();
+        assert_eq!(parsed.field, minefield.field);
+    }
+}
+
+#[test]
+fn test_parse_smaller_minefield() {
+    let minefield: Minefield<30, 16> = BOARD.parse().unwrap();
+    assert_eq!((minefield.width(), minefield.height()), (5, 3));
+    assert_eq!(minefield.to_text().unwrap(), BOARD);
+    assert_eq!(
+        BOARD.parse::<Minefield<4, 3>>(),
+        Err(MinefieldError::InvalidSize)
+    );
+}
+
+#[test]
+fn test_parse_ended_games() {
+    let lost: Minefield<3, 1> = "3x1:1\n1X.".parse().unwrap();
+    assert_eq!(lost.game_state(), GameState::GameOver);
+    let won: Minefield<3, 1> = "3x1:1\n1*1".parse().unwrap();
+    assert_eq!(won.game_state(), GameState::Victory);
+}
+
+#[test]
+fn test_parse_topology() {
+    let text = "4x1:1 torus\n*1.1\n";
+    let minefield: Minefield<4, 1> = text.parse().unwrap();
+    assert_eq!(minefield.topology(), Topology::Toroidal);
+    assert_eq!(minefield.to_text().unwrap(), text);
+    // The last label only touches the mine when wrapping around
+    assert_eq!(
+        "4x1:1\n*1.1".parse::<Minefield<4, 1>>(),
+        Err(MinefieldError::InvalidFormat { line: 2 })
+    );
+    assert_eq!(
+        "4x1:1 hexagon\n*1.1".parse::<Minefield<4, 1>>(),
+        Err(MinefieldError::InvalidFormat { line: 1 })
+    );
+}
+
+#[test]
+fn test_parse_question_marks() {
+    let text = "3x2:2\nQ2q\n.2*\n";
+    let minefield: Minefield<3, 2> = text.parse().unwrap();
+    assert_eq!(minefield.field.get(Coord(0, 0)), Cell::Question);
+    assert_eq!(minefield.field.get(Coord(2, 0)), Cell::Question);
+    assert_eq!(minefield.to_text().unwrap(), text);
+
+    let field: Matrix<Cell, 3, 2> = text.parse().unwrap();
+    assert_eq!(field, minefield.field);
+    assert_eq!(field.to_string(), "3x2\nQ2Q\n.2.\n");
+}
+
+#[test]
+fn test_field_round_trip() {
+    let minefield: Minefield<5, 3> = BOARD.parse().unwrap();
+    let field: Matrix<Cell, 5, 3> = BOARD.parse().unwrap();
+    assert_eq!(field, minefield.field);
+    assert_eq!(field.to_string(), "5x3\n001..\n012F.\n01F..\n");
+    assert_eq!(field.to_string().parse(), Ok(field));
+    assert_eq!(
+        BOARD.parse::<Matrix<Cell, 6, 3>>(),
+        Err(MinefieldError::InvalidSize)
+    );
+}
+
+#[test]
+fn should_fail_on_invalid_format() {
+    let cases = [
+        ("", 1),
+        ("5x3\n", 2),
+        ("5:3\n", 1),
+        ("5x3:a\n", 1),
+        ("5x3:3\n001*.\n012..\n01F..", 1),
+        ("5x3:3\n001*.\n012f\n01F..", 3),
+        ("5x3:3\n001*.\n012f*\n01F.?", 4),
+        ("5x3:3\n001*.\n013f*\n01F..", 3),
+        ("5x3:3\n001*.\n012f*", 4),
+        ("5x3:3\n001*.\n012f*\n01F..\n.....", 5),
+    ];
+    for (text, line) in cases {
+        assert_eq!(
+            text.parse::<Minefield<5, 3>>(),
+            Err(MinefieldError::InvalidFormat { line }),
+            "{:?}",
+            text
+        );
+    }
+    assert_eq!(
+        "0x3:0\n".parse::<Minefield<5, 3>>(),
+        Err(MinefieldError::InvalidSize)
+    );
+}
+
+#[test]
+fn test_parse_final_field() {
+    let field: Matrix<Cell, 5, 1> = "5x1\nXx.1!\n".parse().unwrap();
+    assert_eq!(field.get(Coord(1, 0)), Cell::WrongFlag);
+    assert_eq!(field.get(Coord(4, 0)), Cell::Exploded);
+
+    let minefield: Minefield<5, 1> = "5x1:2\n*x.1!\n".parse().unwrap();
+    assert_eq!(minefield.game_state(), GameState::GameOver);
+    assert_eq!(minefield.field.get(Coord(1, 0)), Cell::Flag);
+    assert_eq!(minefield.final_field(), field);
+}
@@ -0,0 +1,285 @@
+//! Contains a compact human-readable text format for [Minefield]s and for
+//! visible fields ([Matrix] of [Cell]s), so that boards and positions can be
+//! written into tests and attached to bug reports.
+//!
+//! The first line is a header with the size and the amount of mines as
+//! `<width>x<height>:<mines>`, followed by the [Topology] of the minefield
+//! (such as `5x3:2 torus`) unless it is the default [Topology::Moore]. After
+//! the header is one line for every row of the minefield, where every
+//! character is a single tile:
+//! - `.` hidden tile
+//! - `*` hidden tile with a mine
+//! - `F` flagged tile with a mine
+//! - `f` flagged tile without a mine
+//! - `Q` tile with a mine marked with a question mark, [Cell::Question]
+//! - `q` tile without a mine marked with a question mark
+//! - `X` revealed mine
+//! - `!` revealed mine that lost the game, [Cell::Exploded]
+//! - `x` flagged tile without a mine after losing, [Cell::WrongFlag]
+//! - `0` revealed tile without neighbouring mines, [Cell::Empty]
+//! - `1` to `8` revealed label, [Cell::Label]
+//!
+//! For example a 5x3 minefield with 2 mines, where one mine is flagged:
+//! ```
+//! # use miinaharava::minefield::*;
+//! let minefield: Minefield<5, 3> = "5x3:2
+//! 001*.
+//! 012..
+//! 01F..".parse().unwrap();
+//!
+//! assert_eq!(minefield.field.get(Coord(2, 2)), Cell::Flag);
+//! assert_eq!(minefield.to_text().unwrap().parse::<Minefield<5, 3>>(), Ok(minefield));
+//! ```
+//!
+//! A minefield whose mines are only placed on the first reveal (see
+//! [crate::minefield::GenerationPolicy]) can not be written as text before the
+//! first reveal, as the text could not tell where its mines are.
+//!
+//! Visible fields use the same format, but since they do not know where the
+//! mines are, the header has no amount of mines and flags are always `F`. Any
+//! minefield can also be parsed as a visible field, which then results in its
+//! visible field. `!` and `x` only appear in visible fields, namely in
+//! [Minefield::final_field], but they can be parsed as minefields as well,
+//! where they are the same as `X` and `f`.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::minefield::{Cell, Coord, Matrix, Minefield, MinefieldError, Topology};
+
+impl<const W: usize, const H: usize> Minefield<W, H> {
+    /// Write the minefield in the text format, see [crate::text_format].
+    ///
+    /// # Errors
+    /// - [MinefieldError::MinesNotPlaced] if the mines are only placed on the
+    ///   first reveal, which has not happened yet.
+    pub fn to_text(&self) -> Result<String, MinefieldError> {
+        let mut text = format!("{}x{}:{}", self.width(), self.height(), self.mines);
+        if self.topology() != Topology::Moore {
+            text += &format!(" {}", self.topology());
+        }
+        text.push('\n');
+        let mut mines = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let coord = Coord(x, y);
+                let c = match (self.field.get(coord), self.is_mine(coord)) {
+                    (Cell::Hidden, true) => '*',
+                    (Cell::Flag, true) => 'F',
+                    (Cell::Flag, false) => 'f',
+                    (Cell::Question, false) => 'q',
+                    (cell, _) => cell_to_char(cell),
+                };
+                if self.is_mine(coord) {
+                    mines += 1;
+                }
+                text.push(c);
+            }
+            text.push('\n');
+        }
+        if mines != self.mines {
+            return Err(MinefieldError::MinesNotPlaced);
+        }
+        Ok(text)
+    }
+}
+
+impl<const W: usize, const H: usize> FromStr for Minefield<W, H> {
+    type Err = MinefieldError;
+
+    /// Parse a minefield from the text format, see [crate::text_format].
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if the size in the header is zero or
+    ///   larger than the minefield can hold.
+    /// - [MinefieldError::InvalidFormat] if the text is not in the correct
+    ///   format, the amount of mines does not match the header or a label
+    ///   does not match the mines around it.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (header, rows) = parse::<W, H>(text)?;
+
+        let mut mines = Matrix([[false; W]; H]);
+        let mut field = Matrix([[Cell::Empty; W]; H]);
+        for (coord, c, line) in &rows {
+            let (cell, is_mine) = match c {
+                '*' => (Cell::Hidden, true),
+                'F' => (Cell::Flag, true),
+                'f' => (Cell::Flag, false),
+                'Q' => (Cell::Question, true),
+                'q' => (Cell::Question, false),
+                'X' | '!' => (Cell::Mine, true),
+                'x' => (Cell::Flag, false),
+                c => {
+                    let cell =
+                        char_to_cell(*c).ok_or(MinefieldError::InvalidFormat { line: *line })?;
+                    (cell, false)
+                }
+            };
+            mines.set(*coord, is_mine);
+            field.set(*coord, cell);
+        }
+
+        let mut minefield = Minefield::from_parts(header.width, header.height, mines, field);
+        minefield.set_topology(header.topology);
+        if matches!(header.mines, Some(m) if m != minefield.mines) {
+            return Err(MinefieldError::InvalidFormat { line: header.line });
+        }
+        for (coord, _, line) in rows {
+            if let cell @ (Cell::Empty | Cell::Label(_)) = minefield.field.get(coord) {
+                if cell != minefield.cell_contents(coord) {
+                    return Err(MinefieldError::InvalidFormat { line });
+                }
+            }
+        }
+        Ok(minefield)
+    }
+}
+
+impl<const W: usize, const H: usize> Display for Matrix<Cell, W, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}x{}", W, H)?;
+        for row in self.iter() {
+            for cell in row {
+                write!(f, "{}", cell_to_char(*cell))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const W: usize, const H: usize> FromStr for Matrix<Cell, W, H> {
+    type Err = MinefieldError;
+
+    /// Parse a visible field from the text format, see [crate::text_format].
+    /// Text of a minefield can also be parsed, resulting in its visible field.
+    ///
+    /// # Errors
+    /// - [MinefieldError::InvalidSize] if the size in the header is not
+    ///   exactly `W` and `H`.
+    /// - [MinefieldError::InvalidFormat] if the text is not in the correct
+    ///   format.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (header, rows) = parse::<W, H>(text)?;
+        if header.width as usize != W || header.height as usize != H {
+            return Err(MinefieldError::InvalidSize);
+        }
+
+        let mut field = Matrix([[Cell::Empty; W]; H]);
+        for (coord, c, line) in rows {
+            let cell = match c {
+                '*' => Cell::Hidden,
+                'f' => Cell::Flag,
+                'q' => Cell::Question,
+                c => char_to_cell(c).ok_or(MinefieldError::InvalidFormat { line })?,
+            };
+            field.set(coord, cell);
+        }
+        Ok(field)
+    }
+}
+
+/// A parsed tile, with its coordinate, character and the line it was on.
+type Tile<const W: usize, const H: usize> = (Coord<W, H>, char, usize);
+
+/// A parsed header
+struct Header {
+    /// The line the header was on
+    line: usize,
+    /// Width of the minefield
+    width: u16,
+    /// Height of the minefield
+    height: u16,
+    /// Amount of mines, if any
+    mines: Option<u16>,
+    /// Topology of the minefield, [Topology::Moore] if not given
+    topology: Topology,
+}
+
+/// Parse the header and split the rows into tiles, making sure that the size
+/// matches the header.
+fn parse<const W: usize, const H: usize>(
+    text: &str,
+) -> Result<(Header, Vec<Tile<W, H>>), MinefieldError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let (header_line, header) = lines
+        .next()
+        .ok_or(MinefieldError::InvalidFormat { line: 1 })?;
+    let header_error = || MinefieldError::InvalidFormat { line: header_line };
+    let (header, topology) = match header.split_once(' ') {
+        Some((header, topology)) => (header, topology.parse().map_err(|_| header_error())?),
+        None => (header, Topology::default()),
+    };
+    let (size, mines) = match header.split_once(':') {
+        Some((size, mines)) => (size, Some(mines.parse().map_err(|_| header_error())?)),
+        None => (header, None),
+    };
+    let (width, height) = size.split_once('x').ok_or_else(header_error)?;
+    let (width, height): (u16, u16) = match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => return Err(header_error()),
+    };
+    if width == 0 || height == 0 || width as usize > W || height as usize > H {
+        return Err(MinefieldError::InvalidSize);
+    }
+
+    let mut tiles = Vec::with_capacity(width as usize * height as usize);
+    let mut last_line = header_line;
+    for y in 0..height {
+        let (line, row) = lines.next().ok_or(MinefieldError::InvalidFormat {
+            line: last_line + 1,
+        })?;
+        if row.chars().count() != width as usize {
+            return Err(MinefieldError::InvalidFormat { line });
+        }
+        for (x, c) in row.chars().enumerate() {
+            tiles.push((Coord(x as u16, y), c, line));
+        }
+        last_line = line;
+    }
+    if let Some((line, _)) = lines.next() {
+        return Err(MinefieldError::InvalidFormat { line });
+    }
+    let header = Header {
+        line: header_line,
+        width,
+        height,
+        mines,
+        topology,
+    };
+    Ok((header, tiles))
+}
+
+/// Convert a visible cell into its character in the text format
+fn cell_to_char(cell: Cell) -> char {
+    match cell {
+        Cell::Empty => '0',
+        Cell::Label(label) => (b'0' + label) as char,
+        Cell::Flag => 'F',
+        Cell::Hidden => '.',
+        Cell::Question => 'Q',
+        Cell::Mine => 'X',
+        Cell::Exploded => '!',
+        Cell::WrongFlag => 'x',
+    }
+}
+
+/// Convert a character in the text format into a visible cell, None if the
+/// character is not a visible cell.
+fn char_to_cell(c: char) -> Option<Cell> {
+    Some(match c {
+        '0' => Cell::Empty,
+        '1'..='8' => Cell::Label(c as u8 - b'0'),
+        'F' => Cell::Flag,
+        '.' => Cell::Hidden,
+        'Q' => Cell::Question,
+        'X' => Cell::Mine,
+        '!' => Cell::Exploded,
+        'x' => Cell::WrongFlag,
+        _ => None?,
+    })
+}
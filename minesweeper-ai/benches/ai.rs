@@ -1,10 +1,21 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use miinaharava::minefield::{Coord, GameState, Minefield};
+use miinaharava::minefield::{Coord, CustomMinefield, GameState, GenerationPolicy, Minefield};
 use minesweeper_ai::ai::{CSPState, Decision::*};
 
-pub fn benchmark_specific_difficulty<const W: usize, const H: usize>(mines: u8) {
-    let mut minefield = Minefield::<W, H>::generate(mines).unwrap();
-    let mut csp_state = CSPState::default();
+pub fn benchmark_specific_difficulty<const W: usize, const H: usize>(mines: u16) {
+    solve(Minefield::<W, H>::generate(mines).unwrap());
+}
+
+pub fn benchmark_custom_difficulty(width: u16, height: u16, mines: u16) {
+    let seed = rand::random();
+    solve(
+        CustomMinefield::generate_sized(width, height, mines, seed, GenerationPolicy::Random)
+            .unwrap(),
+    );
+}
+
+fn solve<const W: usize, const H: usize>(mut minefield: Minefield<W, H>) {
+    let mut csp_state = CSPState::new(&minefield);
     let mut reveals = Vec::new();
     while minefield.game_state() == GameState::Pending {
         let decisions = csp_state.ponder(reveals.drain(..).collect(), &minefield);
@@ -14,6 +25,7 @@ pub fn benchmark_specific_difficulty<const W: usize, const H: usize>(mines: u8)
                 Flag(c) => minefield.flag(c).ok(),
                 Reveal(c) => minefield.reveal(c).ok(),
                 GuessReveal(c, _) => minefield.reveal(c).ok(),
+                Chord(c) => minefield.chord(c).ok(),
             } {
                 reveals.extend(res);
             }
@@ -31,6 +43,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("expert solving", |b| {
         b.iter(|| benchmark_specific_difficulty::<30, 16>(black_box(99)))
     });
+    c.bench_function("custom 24x24 solving", |b| {
+        b.iter(|| benchmark_custom_difficulty(24, 24, black_box(120)))
+    });
 
     c.bench_function("easy generating", |b| {
         b.iter(|| Minefield::<10, 10>::generate(black_box(10)))
@@ -63,6 +78,20 @@ pub fn criterion_benchmark(c: &mut Criterion) {
                 .reveal(Coord::random())
         })
     });
+    c.bench_function("custom 100x100 revealing", |b| {
+        b.iter(|| {
+            let seed = rand::random();
+            CustomMinefield::generate_sized(
+                100,
+                100,
+                black_box(100),
+                seed,
+                GenerationPolicy::Random,
+            )
+            .unwrap()
+            .reveal(Coord::random())
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -3,7 +3,7 @@
 
 use arrayvec::ArrayVec;
 use bitvec::vec::BitVec;
-use miinaharava::minefield::{Coord, Matrix};
+use miinaharava::minefield::Coord;
 
 use self::solutions::{PossibleSolution, SolutionList};
 
@@ -20,7 +20,7 @@ impl<const W: usize, const H: usize> CoupledSets<W, H> {
     /// of constraints
     pub fn find_viable_solutions(
         &self,
-        remaining_mines: u8,
+        remaining_mines: u16,
         known_minefield: &KnownMinefield<W, H>,
     ) -> Vec<SolutionList<W, H>> {
         let mut solution_lists = Vec::with_capacity(self.0.len());
@@ -34,7 +34,7 @@ impl<const W: usize, const H: usize> CoupledSets<W, H> {
 
         for list in &mut solution_lists {
             let allowed_max_mines = list.min_mines + (remaining_mines - min_mines);
-            for mine_count in (allowed_max_mines + 1)..=(list.solutions_by_mines.len() as u8) {
+            for mine_count in (allowed_max_mines + 1)..=(list.solutions_by_mines.len() as u16) {
                 if let Some(curr) = list.get_mut(mine_count) {
                     curr.clear()
                 }
@@ -46,15 +46,6 @@ impl<const W: usize, const H: usize> CoupledSets<W, H> {
 }
 
 impl<const W: usize, const H: usize> ConstraintSet<W, H> {
-    /// Required for ARRAY_VEC_MATRIX
-    const ARRAY_VEC_CONST: ArrayVec<usize, 8> = ArrayVec::new_const();
-    /// Required for ARRAY_VEC_MATRIX
-    const ARRAY_VEC_CONST_W: [ArrayVec<usize, 8>; W] = [ConstraintSet::<W, H>::ARRAY_VEC_CONST; W];
-    /// An empty WxH matrix where each element is an ArrayVec of usize with a
-    /// max capacity of 8, used for [find_ordered]
-    const ARRAY_VEC_MATRIX: [[ArrayVec<usize, 8>; W]; H] =
-        [ConstraintSet::<W, H>::ARRAY_VEC_CONST_W; H];
-
     /// Form a list from all variables that contain the variable and indexes of
     /// all constraints that include said variable. Sort the list by the amount
     /// of constraints for each variable from most constraints to least.
@@ -63,21 +54,21 @@ impl<const W: usize, const H: usize> ConstraintSet<W, H> {
     /// constraints are modified at any time, the indexes may not work correctly
     /// anymore!
     pub fn find_ordered(&self) -> Vec<(Coord<W, H>, ArrayVec<usize, 8>)> {
-        let mut map = Matrix(ConstraintSet::<W, H>::ARRAY_VEC_MATRIX);
+        // Kept on the heap, since on large minefields a matrix this size would
+        // not fit on the stack.
+        let mut map = vec![ArrayVec::<usize, 8>::new(); W * H];
 
         for (i, constraint) in self.constraints.iter().enumerate() {
             for var in &constraint.variables {
-                map.get_mut_ref(*var).push(i);
+                map[var.1 as usize * W + var.0 as usize].push(i);
             }
         }
 
         let mut ordered = Vec::with_capacity(W * H);
 
-        for (y, row) in map.into_iter().enumerate() {
-            for (x, vec) in row.into_iter().enumerate() {
-                if !vec.is_empty() {
-                    ordered.push((Coord::<W, H>(x as u8, y as u8), vec));
-                }
+        for (i, vec) in map.into_iter().enumerate() {
+            if !vec.is_empty() {
+                ordered.push((Coord::<W, H>((i % W) as u16, (i / W) as u16), vec));
             }
         }
 
@@ -89,15 +80,19 @@ impl<const W: usize, const H: usize> ConstraintSet<W, H> {
     /// Find all the viable solutions only for this specific set of constraints
     pub fn find_viable_solutions(
         &self,
-        remaining_mines: u8,
+        remaining_mines: u16,
         known_field: &KnownMinefield<W, H>,
     ) -> SolutionList<W, H> {
-        let ordered = self.find_ordered();
+        // Variables might already be known if the decisions made about them
+        // have not been acted on yet
+        let mut ordered = self.find_ordered();
+        ordered.retain(|(coord, _)| known_field.get(*coord) == CellContent::Unknown);
 
         let mut solutions = if !ordered.is_empty() {
-            self.find_solutions(&ordered, BitVec::new(), *known_field)
+            self.find_solutions(&ordered, BitVec::new(), &mut known_field.clone())
         } else {
-            Vec::new()
+            // Every variable is already known, so nothing is left to solve
+            vec![BitVec::new()]
         };
         solutions.sort();
         solutions.dedup();
@@ -113,14 +108,15 @@ impl<const W: usize, const H: usize> ConstraintSet<W, H> {
     /// wtih a the specified history. Used in recursion, history can just be
     /// defined as an empty vec at the start, and list should be what
     /// [ConstraintSet::find_ordered] returns. testing_field parameter is simply
-    /// the current status of the known field that is then copied and tested
-    /// against.
+    /// the current status of the known field that is then tested against.
+    /// Guesses are written into testing_field only for the duration of the
+    /// recursion, so it is left unchanged once this returns.
     #[inline]
     pub fn find_solutions(
         &self,
         list: &[(Coord<W, H>, ArrayVec<usize, 8>)],
         history: PossibleSolution,
-        testing_field: KnownMinefield<W, H>,
+        testing_field: &mut KnownMinefield<W, H>,
     ) -> Vec<PossibleSolution> {
         let res2 = self.guess_next(false, list, history.clone(), testing_field);
         let res1 = self.guess_next(true, list, history, testing_field);
@@ -143,27 +139,31 @@ impl<const W: usize, const H: usize> ConstraintSet<W, H> {
         guess: bool,
         list: &[(Coord<W, H>, ArrayVec<usize, 8>)],
         mut history: PossibleSolution,
-        mut testing_field: KnownMinefield<W, H>,
+        testing_field: &mut KnownMinefield<W, H>,
     ) -> Option<Vec<PossibleSolution>> {
         assert!(history.len() < list.len());
         let (coord, idx_vec) = &list[history.len()];
+        let previous = testing_field.get(*coord);
         testing_field.set(*coord, CellContent::Known(guess));
-        for idx in idx_vec {
+        let valid = idx_vec.iter().all(|idx| {
             let constraint = &self.constraints[*idx];
-            let (hidden, mines) = constraint_counts(constraint, &testing_field);
-
-            if constraint.label > (hidden + mines) || mines > constraint.label {
-                None?;
-            }
-        }
-        history.push(guess);
-        if history.len() >= list.len() {
-            let mut returned = Vec::with_capacity(list.len());
-            returned.push(history);
-            Some(returned)
+            let (hidden, mines) = constraint_counts(constraint, testing_field);
+            constraint.label <= (hidden + mines) && mines <= constraint.label
+        });
+        let result = if !valid {
+            None
         } else {
-            Some(self.find_solutions(list, history, testing_field))
-        }
+            history.push(guess);
+            if history.len() >= list.len() {
+                let mut returned = Vec::with_capacity(list.len());
+                returned.push(history);
+                Some(returned)
+            } else {
+                Some(self.find_solutions(list, history, testing_field))
+            }
+        };
+        testing_field.set(*coord, previous);
+        result
     }
 }
 
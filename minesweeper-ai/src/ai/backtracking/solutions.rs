@@ -20,10 +20,6 @@ pub trait SolutionContainer<const W: usize, const H: usize> {
     /// that has the greatest propability is the most likely then to be empty of
     /// a mine, and therefore the best guess.
     fn find_best_guess(&self) -> (Coord<W, H>, f32);
-    /// Returns the minimum number of mines for these solutions
-    fn min_mines(&self) -> u8;
-    /// Returns the maximum number of mines for these solutions
-    fn max_mines(&self) -> u8;
 }
 
 #[derive(Debug, Clone)]
@@ -35,9 +31,9 @@ pub struct SolutionList<const W: usize, const H: usize> {
     /// an array of the solutions that contain this amount of mines.
     pub solutions_by_mines: Vec<Vec<PossibleSolution>>,
     /// The smallest amount of mines in any solution
-    pub min_mines: u8,
+    pub min_mines: u16,
     /// The largest amount of mines
-    pub max_mines: u8,
+    pub max_mines: u16,
     /// The coordinates that the solutions indexes reflect.
     pub coords: Vec<Coord<W, H>>,
 }
@@ -49,7 +45,7 @@ impl<const W: usize, const H: usize> SolutionList<W, H> {
     pub fn from(
         solutions: Vec<PossibleSolution>,
         coords: Vec<Coord<W, H>>,
-        remaining_mines: u8,
+        remaining_mines: u16,
     ) -> SolutionList<W, H> {
         let mut solution_list = SolutionList {
             solutions_by_mines: vec![Vec::new(); (remaining_mines + 1) as usize],
@@ -58,7 +54,7 @@ impl<const W: usize, const H: usize> SolutionList<W, H> {
             coords,
         };
         for solution in solutions {
-            let mine_count = solution.count_ones() as u8;
+            let mine_count = solution.count_ones() as u16;
             if mine_count <= remaining_mines {
                 solution_list.solutions_by_mines[mine_count as usize].push(solution);
                 solution_list.min_mines = solution_list.min_mines.min(mine_count);
@@ -73,7 +69,7 @@ impl<const W: usize, const H: usize> SolutionList<W, H> {
     /// are no solutions for that amount of mines, Some if there might be. Used
     /// only in tests.
     #[cfg(test)]
-    pub fn get(&self, mine_count: u8) -> Option<&Vec<PossibleSolution>> {
+    pub fn get(&self, mine_count: u16) -> Option<&Vec<PossibleSolution>> {
         if self.min_mines > mine_count || mine_count > self.max_mines {
             None
         } else {
@@ -81,10 +77,24 @@ impl<const W: usize, const H: usize> SolutionList<W, H> {
         }
     }
 
+    /// Returns the minimum number of mines for these solutions. Used only in
+    /// tests.
+    #[cfg(test)]
+    pub fn min_mines(&self) -> u16 {
+        self.min_mines
+    }
+
+    /// Returns the maximum number of mines for these solutions. Used only in
+    /// tests.
+    #[cfg(test)]
+    pub fn max_mines(&self) -> u16 {
+        self.max_mines
+    }
+
     /// Safely get a list of solutions mutably for any amount of mines. None if
     /// there are no solutions for that amount of mines, Some if there might be.
     /// Used only in tests.
-    pub fn get_mut(&mut self, mine_count: u8) -> Option<&mut Vec<PossibleSolution>> {
+    pub fn get_mut(&mut self, mine_count: u16) -> Option<&mut Vec<PossibleSolution>> {
         if self.min_mines > mine_count || mine_count > self.max_mines {
             None
         } else {
@@ -177,14 +187,6 @@ impl<const W: usize, const H: usize> SolutionContainer<W, H> for SolutionList<W,
 
         best_guess.unwrap()
     }
-
-    fn max_mines(&self) -> u8 {
-        self.max_mines
-    }
-
-    fn min_mines(&self) -> u8 {
-        self.min_mines
-    }
 }
 
 impl<const W: usize, const H: usize, T: SolutionContainer<W, H>> SolutionContainer<W, H>
@@ -205,20 +207,4 @@ impl<const W: usize, const H: usize, T: SolutionContainer<W, H>> SolutionContain
         }
         best_guess.unwrap()
     }
-
-    fn min_mines(&self) -> u8 {
-        let mut min_mines = 0;
-        for solution in self {
-            min_mines += solution.min_mines()
-        }
-        min_mines
-    }
-
-    fn max_mines(&self) -> u8 {
-        let mut min_mines = 0;
-        for solution in self {
-            min_mines += solution.max_mines()
-        }
-        min_mines
-    }
 }
@@ -81,7 +81,7 @@ impl<const W: usize, const H: usize> CoupledSets<W, H> {
         for (y, row) in known_minefield.iter().enumerate() {
             for (x, item) in row.iter().enumerate() {
                 if let CellContent::Unknown = item {
-                    unconstrained.insert(Coord(x as u8, y as u8));
+                    unconstrained.insert(Coord(x as u16, y as u16));
                 }
             }
         }
@@ -231,6 +231,7 @@ impl<const W: usize, const H: usize> ConstraintSet<W, H> {
                 Decision::Reveal(c) | Decision::Flag(c) | Decision::GuessReveal(c, _) => {
                     self.variables.remove(*c)
                 }
+                Decision::Chord(_) => {}
             }
         }
 
@@ -15,6 +15,8 @@ pub struct Constraint<const W: usize, const H: usize> {
     /// Value or label for the variables
     pub label: u8,
     /// List of coordinates to represent the variables that add up to the label.
+    /// There are at most as many variables as a tile has neighbours, so 8 on
+    /// most topologies and 6 on a hexagonal minefield.
     pub variables: ArrayVec<Coord<W, H>, 8>,
 }
 
@@ -31,35 +31,30 @@ impl<const W: usize, const H: usize> CoordSet<W, H> {
         }
     }
 
-    /// Returns a boolean slice of length W, for which all values are the specified
-    /// param, except for the very beginning and end.
-    const fn row(middle: bool) -> [bool; W] {
-        let mut row = [middle; W];
-        row[0] = !middle;
-        row[W - 1] = !middle;
-        row
-    }
-
-    /// Returns a [CoordSet] where only the corners exist.
-    pub const fn corners() -> CoordSet<W, H> {
-        let mut c = CoordSet {
-            matrix: Matrix([[false; W]; H]),
-        };
-        c.matrix.0[0] = CoordSet::<W, H>::row(false);
-        c.matrix.0[H - 1] = CoordSet::<W, H>::row(false);
+    /// Returns a [CoordSet] where only the corners of the top-left area of the
+    /// given size exist.
+    pub fn corners(width: u16, height: u16) -> CoordSet<W, H> {
+        let mut c = CoordSet::from(false);
+        for y in [0, height - 1] {
+            for x in [0, width - 1] {
+                c.insert(Coord(x, y));
+            }
+        }
         c
     }
 
-    /// Returns a [CoordSet] where only the edges exist, but not the corners.
-    pub const fn edges() -> CoordSet<W, H> {
-        let default = CoordSet::<W, H>::row(false);
-        let mut c = CoordSet {
-            matrix: Matrix([default; H]),
-        };
-        let top_bottom = CoordSet::<W, H>::row(true);
-
-        c.matrix.0[0] = top_bottom;
-        c.matrix.0[H - 1] = top_bottom;
+    /// Returns a [CoordSet] where only the edges of the top-left area of the
+    /// given size exist, but not the corners.
+    pub fn edges(width: u16, height: u16) -> CoordSet<W, H> {
+        let mut c = CoordSet::from(false);
+        for x in 1..width.saturating_sub(1) {
+            c.insert(Coord(x, 0));
+            c.insert(Coord(x, height - 1));
+        }
+        for y in 1..height.saturating_sub(1) {
+            c.insert(Coord(0, y));
+            c.insert(Coord(width - 1, y));
+        }
         c
     }
 
@@ -133,7 +128,7 @@ impl<const W: usize, const H: usize> CoordSet<W, H> {
             row.iter()
                 .enumerate()
                 .filter(|(_, c)| **c)
-                .map(move |(x, _)| Coord(x as u8, y as u8))
+                .map(move |(x, _)| Coord(x as u16, y as u16))
         })
     }
 
@@ -190,7 +185,7 @@ impl<const W: usize, const H: usize> CoordSet<W, H> {
             row.iter_mut()
                 .enumerate()
                 .filter(|(_, c)| **c)
-                .map(move |(x, c)| (c, Coord(x as u8, y as u8)))
+                .map(move |(x, c)| (c, Coord(x as u16, y as u16)))
         })
     }
 
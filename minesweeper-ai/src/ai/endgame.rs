@@ -0,0 +1,305 @@
+//! This module finds the best move of small endgames exactly, represented by
+//! [EndgameMove].
+//!
+//! Once only a few hidden cells remain, every layout of the remaining mines
+//! that fits the constraints can be listed, and every one of them is equally
+//! likely. Revealing a cell then splits the layouts where it is safe by what
+//! the reveal shows: the label of the cell, and the labels of every cell that
+//! is revealed along with it if it is an opening. The search tries every
+//! possible reveal in every position that can follow, and chooses the reveal
+//! that wins the most of the layouts in the end, which is not always the
+//! safest one.
+//!
+//! As the search grows very quickly with the amount of hidden cells, it is
+//! given up if there are too many layouts or positions to go through, in
+//! which case the guess is made as usual.
+
+use std::collections::HashMap;
+
+use miinaharava::minefield::{Coord, Minefield};
+
+use super::{
+    backtracking::solutions::SolutionList, coord_set::CoordSet, CellContent, KnownMinefield,
+};
+
+/// The default largest amount of hidden cells for which the endgame is
+/// searched exhaustively.
+pub const DEFAULT_ENDGAME_SIZE: usize = 16;
+
+/// The most layouts of the remaining mines that are searched through.
+const MAX_LAYOUTS: usize = 2000;
+
+/// The most positions that are searched through before the search is given
+/// up.
+const MAX_POSITIONS: usize = 100_000;
+
+/// The best move of an endgame, see [crate::ai::endgame].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EndgameMove<const W: usize, const H: usize> {
+    /// The cell to reveal
+    pub coord: Coord<W, H>,
+    /// The probability of the cell being safe
+    pub safety: f64,
+    /// The probability of winning the game when playing the best moves from
+    /// here on
+    pub win_probability: f64,
+}
+
+/// Search for the move that is the most likely to win the game, given the
+/// solutions of every set of constraints, the unconstrained variables and the
+/// amount of remaining mines. Returns None if there are more hidden cells than
+/// `size`, if there is only a single layout left or if the search would take
+/// too long.
+pub fn best_move<const W: usize, const H: usize>(
+    solution_lists: &[SolutionList<W, H>],
+    unconstrained: &CoordSet<W, H>,
+    remaining_mines: u16,
+    known_fields: &KnownMinefield<W, H>,
+    minefield: &Minefield<W, H>,
+    size: usize,
+) -> Option<EndgameMove<W, H>> {
+    let unconstrained = unconstrained.iter().collect::<Vec<_>>();
+    let cells = solution_lists
+        .iter()
+        .flat_map(|list| list.coords.iter().copied())
+        .chain(unconstrained.iter().copied())
+        .collect::<Vec<_>>();
+    if cells.is_empty() || cells.len() > size.min(u64::BITS as usize) {
+        return None;
+    }
+
+    let mut layouts = Vec::new();
+    let unconstrained_bits = (cells.len() - unconstrained.len()..cells.len()).collect::<Vec<_>>();
+    if !list_layouts(
+        solution_lists,
+        &unconstrained_bits,
+        remaining_mines as usize,
+        (0, 0, 0),
+        &mut layouts,
+    ) || layouts.len() < 2
+    {
+        return None;
+    }
+    layouts.sort_unstable();
+
+    let mut search = Search::new(&cells, known_fields, minefield);
+    let (cell, win_probability) = search.best_move(0, &layouts);
+    if search.positions > MAX_POSITIONS {
+        return None;
+    }
+    let safe = layouts.iter().filter(|l| *l & (1 << cell) == 0).count();
+    Some(EndgameMove {
+        coord: cells[cell],
+        safety: safe as f64 / layouts.len() as f64,
+        win_probability,
+    })
+}
+
+/// List every layout of the mines as a bitmask of the cells, combining a
+/// solution from every list with the rest of the mines in the unconstrained
+/// cells. `layout` is the layout so far, the index of its next bit and the
+/// amount of mines in it. Returns false if there are more than [MAX_LAYOUTS]
+/// layouts.
+fn list_layouts<const W: usize, const H: usize>(
+    solution_lists: &[SolutionList<W, H>],
+    unconstrained_bits: &[usize],
+    remaining_mines: usize,
+    (layout, bit, mines): (u64, usize, usize),
+    layouts: &mut Vec<u64>,
+) -> bool {
+    let Some((list, rest)) = solution_lists.split_first() else {
+        let Some(unconstrained_mines) = remaining_mines.checked_sub(mines) else {
+            return true;
+        };
+        return list_combinations(unconstrained_bits, unconstrained_mines, layout, layouts);
+    };
+    for (solution_mines, solutions) in list.solutions_by_mines.iter().enumerate() {
+        for solution in solutions {
+            let solution_layout = solution
+                .iter_ones()
+                .fold(layout, |layout, idx| layout | 1 << (bit + idx));
+            if !list_layouts(
+                rest,
+                unconstrained_bits,
+                remaining_mines,
+                (
+                    solution_layout,
+                    bit + list.coords.len(),
+                    mines + solution_mines,
+                ),
+                layouts,
+            ) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Add every way to place the given amount of mines in the given bits to the
+/// layout. Returns false if there are more than [MAX_LAYOUTS] layouts.
+fn list_combinations(bits: &[usize], mines: usize, layout: u64, layouts: &mut Vec<u64>) -> bool {
+    if mines == 0 {
+        layouts.push(layout);
+        return layouts.len() <= MAX_LAYOUTS;
+    }
+    for (i, bit) in bits.iter().enumerate() {
+        if bits.len() - i < mines {
+            break;
+        }
+        if !list_combinations(&bits[i + 1..], mines - 1, layout | 1 << bit, layouts) {
+            return false;
+        }
+    }
+    true
+}
+
+/// The state of a single search through the endgame. Cells are referred to by
+/// their bit in the layouts.
+struct Search {
+    /// The hidden neighbours of every cell
+    neighbours: Vec<u64>,
+    /// The amount of neighbours of every cell that are known to be mines
+    known_mines: Vec<u8>,
+    /// The win probability of every position that is already searched, by the
+    /// revealed cells and the layouts that are still possible
+    positions_searched: HashMap<(u64, Vec<u64>), f64>,
+    /// The amount of positions searched so far
+    positions: usize,
+}
+
+impl Search {
+    /// Create a search for the given hidden cells.
+    fn new<const W: usize, const H: usize>(
+        cells: &[Coord<W, H>],
+        known_fields: &KnownMinefield<W, H>,
+        minefield: &Minefield<W, H>,
+    ) -> Search {
+        let mut neighbours = Vec::with_capacity(cells.len());
+        let mut known_mines = Vec::with_capacity(cells.len());
+        for cell in cells {
+            let mut hidden = 0;
+            let mut mines = 0;
+            for neighbour in minefield.neighbours(*cell) {
+                if let Some(bit) = cells.iter().position(|c| *c == neighbour) {
+                    hidden |= 1 << bit;
+                } else if known_fields.get(neighbour) == CellContent::Known(true) {
+                    mines += 1;
+                }
+            }
+            neighbours.push(hidden);
+            known_mines.push(mines);
+        }
+        Search {
+            neighbours,
+            known_mines,
+            positions_searched: HashMap::new(),
+            positions: 0,
+        }
+    }
+
+    /// The probability of winning from the position where the given cells are
+    /// revealed and the given layouts are still possible.
+    fn win_probability(&mut self, revealed: u64, layouts: Vec<u64>) -> f64 {
+        if layouts.len() == 1 {
+            return 1.;
+        }
+        let key = (revealed, layouts);
+        if let Some(probability) = self.positions_searched.get(&key) {
+            return *probability;
+        }
+        self.positions += 1;
+        if self.positions > MAX_POSITIONS {
+            return 0.;
+        }
+        let (_, probability) = self.best_move(revealed, &key.1);
+        self.positions_searched.insert(key, probability);
+        probability
+    }
+
+    /// Return the cell to reveal that is the most likely to win from the given
+    /// position, along with the probability of winning.
+    fn best_move(&mut self, revealed: u64, layouts: &[u64]) -> (usize, f64) {
+        let any_mines = layouts.iter().fold(0, |a, b| a | b);
+        let hidden = self.neighbours.len();
+
+        // Revealing a cell that is safe in every layout can never hurt
+        if let Some(cell) = (0..hidden).find(|c| (revealed | any_mines) & 1 << c == 0) {
+            return (cell, self.reveal_probability(cell, revealed, layouts));
+        }
+
+        // A move can not win more often than it is safe, so the safest moves
+        // are searched first and the rest are skipped once they can not win
+        // more often than the best move found so far
+        let mut candidates = (0..hidden)
+            .filter(|c| revealed & 1 << c == 0)
+            .map(|c| {
+                let safe = layouts.iter().filter(|l| *l & 1 << c == 0).count();
+                (c, safe as f64 / layouts.len() as f64)
+            })
+            .filter(|(_, safety)| *safety > 0.)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut best = (candidates[0].0, 0.);
+        for (cell, safety) in candidates {
+            if safety <= best.1 || self.positions > MAX_POSITIONS {
+                break;
+            }
+            let probability = self.reveal_probability(cell, revealed, layouts);
+            if probability > best.1 {
+                best = (cell, probability);
+            }
+        }
+        best
+    }
+
+    /// The probability of winning after revealing the given cell, when the
+    /// best moves are played after it.
+    fn reveal_probability(&mut self, cell: usize, revealed: u64, layouts: &[u64]) -> f64 {
+        // Layouts where the cell is safe, by what the reveal shows
+        let mut outcomes: HashMap<(u64, Vec<u8>), Vec<u64>> = HashMap::new();
+        for layout in layouts.iter().filter(|l| *l & 1 << cell == 0) {
+            outcomes
+                .entry(self.reveal(cell, *layout))
+                .or_default()
+                .push(*layout);
+        }
+
+        outcomes
+            .into_iter()
+            .map(|((newly_revealed, _), outcome_layouts)| {
+                let weight = outcome_layouts.len() as f64;
+                weight * self.win_probability(revealed | newly_revealed, outcome_layouts)
+            })
+            .sum::<f64>()
+            / layouts.len() as f64
+    }
+
+    /// Reveal the given cell in the given layout, where the cell is safe.
+    /// Returns the revealed cells, which are more than just the cell itself if
+    /// it is an opening, and the labels of the revealed cells in order.
+    fn reveal(&self, cell: usize, layout: u64) -> (u64, Vec<u8>) {
+        let mut revealed = 1 << cell;
+        let mut stack = vec![cell];
+        while let Some(c) = stack.pop() {
+            if self.known_mines[c] == 0 && layout & self.neighbours[c] == 0 {
+                let mut opened = self.neighbours[c] & !revealed;
+                revealed |= opened;
+                while opened != 0 {
+                    stack.push(opened.trailing_zeros() as usize);
+                    opened &= opened - 1;
+                }
+            }
+        }
+
+        let mut labels = Vec::new();
+        let mut remaining = revealed;
+        while remaining != 0 {
+            let c = remaining.trailing_zeros() as usize;
+            labels.push((layout & self.neighbours[c]).count_ones() as u8);
+            remaining &= remaining - 1;
+        }
+        (revealed, labels)
+    }
+}
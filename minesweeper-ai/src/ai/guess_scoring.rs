@@ -0,0 +1,195 @@
+//! This module scores the possible guesses not only by how likely they are to
+//! be safe, but also by how much they are likely to reveal, represented by
+//! [GuessScore].
+//!
+//! A guess is worth more when
+//! - it is likely to be an opening, so that every one of its neighbours is
+//!   safe and revealed along with it,
+//! - it borders more sets of constraints, as its label will then tell
+//!   something about all of them,
+//! - the move after it is likely to be safe as well, if it turns out to be
+//!   safe.
+//!
+//! Safety still matters the most: a guess that gains as much information as
+//! possible is only preferred over a guess that is at most
+//! [GAIN_WEIGHT] safer.
+
+use miinaharava::minefield::{Coord, Matrix, Minefield};
+
+use super::{
+    backtracking::solutions::SolutionList, probabilities::MineProbabilities, KnownMinefield,
+};
+
+/// How much the information gained can make up for a guess being less safe,
+/// see [crate::ai::guess_scoring].
+pub const GAIN_WEIGHT: f64 = 0.02;
+
+/// The amount of bordering sets of constraints after which a guess is not
+/// worth more for bordering even more of them.
+const MAX_SETS: usize = 4;
+
+/// A single possible guess and how much it is worth, see
+/// [crate::ai::guess_scoring].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuessScore<const W: usize, const H: usize> {
+    /// The guessed coordinate
+    pub coord: Coord<W, H>,
+    /// The probability of the guess being safe
+    pub safety: f64,
+    /// The probability of the guess being an opening, estimated as if its
+    /// neighbours were independent of each other.
+    pub opening: f64,
+    /// How many sets of constraints the guess or its neighbours are in
+    pub sets: usize,
+    /// The probability of the safest next guess being safe, if this guess is
+    /// safe.
+    pub next_safety: f64,
+}
+
+impl<const W: usize, const H: usize> GuessScore<W, H> {
+    /// How much information the guess is likely to gain, between 0 and 1.
+    pub fn gain(&self) -> f64 {
+        let sets = self.sets.min(MAX_SETS) as f64 / MAX_SETS as f64;
+        (self.opening + sets + self.next_safety) / 3.
+    }
+
+    /// The total worth of the guess, combining its safety with its gain.
+    pub fn score(&self) -> f64 {
+        self.safety * (1. + GAIN_WEIGHT * self.gain())
+    }
+}
+
+/// Score every variable that could be the best guess, both constrained and
+/// unconstrained ones, from the probabilities calculated from the given
+/// solutions. Variables that are so much less safe than the safest one that
+/// no gain could make up for it are not scored at all.
+pub fn score_guesses<const W: usize, const H: usize>(
+    probabilities: &MineProbabilities<W, H>,
+    solution_lists: &[SolutionList<W, H>],
+    known_fields: &KnownMinefield<W, H>,
+    minefield: &Minefield<W, H>,
+) -> Vec<GuessScore<W, H>> {
+    let matrix = probabilities.matrix(known_fields);
+
+    // Which set every constrained variable is in
+    let mut set_indexes = Matrix::from(None);
+    for (i, list) in solution_lists.iter().enumerate() {
+        for coord in &list.coords {
+            set_indexes.set(*coord, Some(i));
+        }
+    }
+
+    let unconstrained_safety = if probabilities.unconstrained.is_empty() {
+        0.
+    } else {
+        1. - probabilities.unconstrained_probability
+    };
+    let safest_constrained = probabilities
+        .safest_constrained()
+        .map_or(0., |(_, probability)| 1. - probability);
+    let is_candidate =
+        |safety: f64| safety * (1. + GAIN_WEIGHT) >= safest_constrained.max(unconstrained_safety);
+
+    let score = |coord: Coord<W, H>, next_safety: f64| {
+        let neighbours = minefield.neighbours(coord);
+        let opening = neighbours
+            .iter()
+            .map(|c| 1. - matrix.get(*c) as f64)
+            .product::<f64>();
+        let mut sets = neighbours
+            .iter()
+            .chain([coord].iter())
+            .filter_map(|c| set_indexes.get(*c))
+            .collect::<Vec<_>>();
+        sets.sort();
+        sets.dedup();
+        GuessScore {
+            coord,
+            safety: 1. - matrix.get(coord) as f64,
+            opening,
+            sets: sets.len(),
+            next_safety,
+        }
+    };
+
+    let mut scores = Vec::new();
+    for (i, list) in solution_lists.iter().enumerate() {
+        let candidates = (0..list.coords.len())
+            .filter(|idx| is_candidate(1. - matrix.get(list.coords[*idx]) as f64))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            continue;
+        }
+        let next_safeties = next_safeties(list, &probabilities.solution_weights[i], &candidates);
+        for (idx, next_safety) in candidates.into_iter().zip(next_safeties) {
+            scores.push(score(
+                list.coords[idx],
+                next_safety.max(unconstrained_safety),
+            ));
+        }
+    }
+    if is_candidate(unconstrained_safety) {
+        let next_safety = safest_constrained.max(unconstrained_safety);
+        for coord in probabilities.unconstrained.iter() {
+            scores.push(score(coord, next_safety));
+        }
+    }
+    scores
+}
+
+/// Return the guess with the best score, see [score_guesses].
+pub fn best_guess<const W: usize, const H: usize>(
+    probabilities: &MineProbabilities<W, H>,
+    solution_lists: &[SolutionList<W, H>],
+    known_fields: &KnownMinefield<W, H>,
+    minefield: &Minefield<W, H>,
+) -> Option<GuessScore<W, H>> {
+    score_guesses(probabilities, solution_lists, known_fields, minefield)
+        .into_iter()
+        .max_by(|a, b| a.score().total_cmp(&b.score()))
+}
+
+/// For the variables of the set at the given indexes, the probability of the
+/// safest other variable of the same set being safe, given that the variable
+/// itself is safe. 0 if there are no other variables, or if the variable can
+/// not be safe.
+fn next_safeties<const W: usize, const H: usize>(
+    list: &SolutionList<W, H>,
+    solution_weights: &[f64],
+    indexes: &[usize],
+) -> Vec<f64> {
+    // For every given variable, the total weight of the solutions where it is
+    // safe, and where it is safe but another variable is a mine
+    let len = list.coords.len();
+    let mut safe_weights = vec![0.; indexes.len()];
+    let mut mine_weights = vec![vec![0.; len]; indexes.len()];
+    for (mines, solutions) in list.solutions_by_mines.iter().enumerate() {
+        let weight = solution_weights[mines];
+        for solution in solutions {
+            for (i, idx) in indexes.iter().enumerate() {
+                if !solution[*idx] {
+                    safe_weights[i] += weight;
+                    for j in solution.iter_ones() {
+                        mine_weights[i][j] += weight;
+                    }
+                }
+            }
+        }
+    }
+
+    indexes
+        .iter()
+        .enumerate()
+        .map(|(i, idx)| {
+            if safe_weights[i] <= 0. {
+                return 0.;
+            }
+            mine_weights[i]
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| j != idx)
+                .map(|(_, weight)| 1. - weight / safe_weights[i])
+                .fold(0., f64::max)
+        })
+        .collect()
+}
@@ -6,14 +6,21 @@ use fixed::{types::extra::U20, FixedU32};
 use miinaharava::minefield::{Cell, Coord, Matrix, Minefield, Reveal};
 use rand::seq::SliceRandom;
 
-use crate::ai::backtracking::solutions::SolutionContainer;
+use crate::ai::backtracking::solutions::{SolutionContainer, SolutionList};
 
-use self::{constraint_sets::CoupledSets, constraints::Constraint, coord_set::CoordSet};
+use self::{
+    constraint_sets::CoupledSets, constraints::Constraint, coord_set::CoordSet,
+    endgame::DEFAULT_ENDGAME_SIZE, probabilities::MineProbabilities,
+};
 
 pub mod backtracking;
 pub mod constraint_sets;
 pub mod constraints;
 pub mod coord_set;
+pub mod endgame;
+pub mod guess_scoring;
+pub mod no_guess;
+pub mod probabilities;
 
 #[cfg(test)]
 mod tests;
@@ -38,22 +45,68 @@ pub enum Decision<const W: usize, const H: usize> {
     /// Reveal this coordinate, but this reveal was actually guessed with
     /// propability the fixed point decimal
     GuessReveal(Coord<W, H>, FixedU32<U20>),
+    /// Chord this coordinate, revealing all of its hidden unflagged neighbours
+    /// at once.
+    Chord(Coord<W, H>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// How the AI chooses where to guess, when it has to
+pub enum GuessStrategy {
+    /// Guess the cell that is the most likely to be safe
+    #[default]
+    Safest,
+    /// Guess the cell with the best score, which also weighs how much the guess
+    /// is likely to reveal, see [guess_scoring]
+    Scored,
 }
 
 /// Represents the AI state's own opinion on fields
 pub type KnownMinefield<const W: usize, const H: usize> = Matrix<CellContent, W, H>;
 
 /// General state used for solving Constraint Satisfication Problem
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct CSPState<const W: usize, const H: usize> {
     /// List of label-mine-location-constraints for a given state
     pub constraint_sets: CoupledSets<W, H>,
     /// Represents the current state of the minefield, according to the AI. Not
     /// guarenteed to be correct.
     pub known_fields: KnownMinefield<W, H>,
+    /// How guesses are chosen
+    pub guess_strategy: GuessStrategy,
+    /// The largest amount of hidden cells for which the guesses are searched
+    /// exhaustively for the best chance of winning, see [endgame]. 0 never
+    /// searches.
+    pub endgame_size: usize,
+}
+
+impl<const W: usize, const H: usize> Default for CSPState<W, H> {
+    fn default() -> Self {
+        CSPState {
+            constraint_sets: CoupledSets::default(),
+            known_fields: KnownMinefield::default(),
+            guess_strategy: GuessStrategy::default(),
+            endgame_size: DEFAULT_ENDGAME_SIZE,
+        }
+    }
 }
 
 impl<const W: usize, const H: usize> CSPState<W, H> {
+    /// Create a new state for solving the given minefield. If the minefield is
+    /// smaller than `W` and `H`, the tiles outside of it are already known to
+    /// not be mines.
+    pub fn new(minefield: &Minefield<W, H>) -> Self {
+        let mut state = CSPState::default();
+        for (y, row) in state.known_fields.0.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if !minefield.contains(Coord(x as u16, y as u16)) {
+                    *cell = CellContent::Known(false);
+                }
+            }
+        }
+        state
+    }
+
     /// Ponder on the current state of the game, either making decisions over
     /// "trivial" problems, or by [CSPState::perform_educated_guess]
     pub fn ponder(
@@ -61,8 +114,13 @@ impl<const W: usize, const H: usize> CSPState<W, H> {
         reveals: Vec<Reveal<W, H>>,
         minefield: &Minefield<W, H>,
     ) -> Vec<Decision<W, H>> {
-        // Set all newly revealed tiles as known
+        // Set all newly revealed tiles as known, flags only tell what the
+        // flagged tile is now
         let mut decisions = Vec::new();
+        let reveals = reveals
+            .into_iter()
+            .filter(|(_, cell)| !cell.is_hidden() && *cell != Cell::Flag)
+            .collect::<Vec<_>>();
         for (coord, cell) in &reveals {
             self.known_fields
                 .set(*coord, CellContent::Known(*cell == Cell::Mine))
@@ -72,13 +130,13 @@ impl<const W: usize, const H: usize> CSPState<W, H> {
         for (coord, cell) in &reveals {
             if let Cell::Label(mut label) = cell {
                 let mut neighbors = ArrayVec::new();
-                for neighbor in coord.neighbours().iter() {
+                for neighbor in minefield.neighbours(*coord).iter() {
                     match (
                         minefield.field.get(*neighbor),
                         self.known_fields.get(*neighbor),
                     ) {
                         (Cell::Flag, _) | (_, CellContent::Known(true)) => label -= 1,
-                        (Cell::Hidden, _) => neighbors.push(*neighbor),
+                        (cell, _) if cell.is_hidden() => neighbors.push(*neighbor),
                         _ => {}
                     }
                 }
@@ -129,30 +187,82 @@ impl<const W: usize, const H: usize> CSPState<W, H> {
         decisions.sort();
         decisions.dedup();
         decisions.retain(|decision| match decision {
-            Decision::Flag(c) => minefield.field.get(*c) == Cell::Hidden,
+            Decision::Flag(c) => minefield.field.get(*c).is_hidden(),
             Decision::Reveal(c) | Decision::GuessReveal(c, _) => {
                 !matches!(minefield.field.get(*c), Cell::Empty | Cell::Label(_))
             }
+            Decision::Chord(_) => true,
         });
 
         // If no decisions could be made, make an educated guess.
         if decisions.is_empty() {
             self.perform_educated_guess(minefield)
         } else {
-            decisions
+            CSPState::chord_reveals(decisions, minefield)
         }
     }
 
+    /// Replace reveals with chords wherever a single chord on a label would
+    /// reveal at least two of the revealed tiles at once, like a human player
+    /// would. Only labels where all the mines are already flagged on the
+    /// minefield are chorded, so the order in which the decisions are acted
+    /// on does not matter.
+    fn chord_reveals(
+        decisions: Vec<Decision<W, H>>,
+        minefield: &Minefield<W, H>,
+    ) -> Vec<Decision<W, H>> {
+        let mut reveals = CoordSet::default();
+        for decision in &decisions {
+            if let Decision::Reveal(c) = decision {
+                reveals.insert(*c);
+            }
+        }
+
+        let mut chords = Vec::new();
+        for decision in &decisions {
+            let Decision::Reveal(coord) = decision else {
+                continue;
+            };
+            if !reveals.contains(*coord) {
+                continue;
+            }
+            for label_coord in minefield.neighbours(*coord) {
+                if let Cell::Label(label) = minefield.field.get(label_coord) {
+                    let neighbours = minefield.neighbours(label_coord);
+                    let flags = neighbours
+                        .iter()
+                        .filter(|c| minefield.field.get(**c) == Cell::Flag)
+                        .count();
+                    let hidden = neighbours
+                        .into_iter()
+                        .filter(|c| minefield.field.get(*c).is_hidden())
+                        .collect::<ArrayVec<_, 8>>();
+                    if flags == label as usize
+                        && hidden.len() >= 2
+                        && hidden.iter().all(|c| reveals.contains(*c))
+                    {
+                        for c in hidden {
+                            reveals.remove(c);
+                        }
+                        chords.push(Decision::Chord(label_coord));
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut decisions = decisions
+            .into_iter()
+            .filter(|d| !matches!(d, Decision::Reveal(c) if !reveals.contains(*c)))
+            .collect::<Vec<_>>();
+        decisions.extend(chords);
+        decisions
+    }
+
     /// Perform an educated guess on where to reveal next based on the current
     /// state of the game.
     pub fn perform_educated_guess(&mut self, minefield: &Minefield<W, H>) -> Vec<Decision<W, H>> {
-        let found_mines = self
-            .known_fields
-            .iter()
-            .flatten()
-            .filter(|c| **c == CellContent::Known(true))
-            .count() as u8;
-        let remaining_mines = minefield.mines - found_mines;
+        let remaining_mines = self.remaining_mines(minefield);
 
         // Find all viable solutions
         let solution_lists = self
@@ -175,28 +285,57 @@ impl<const W: usize, const H: usize> CSPState<W, H> {
                 }
                 trivials
             } else {
-                // No trivial solutions, find best guess for given solutions
-                let mut best_guess = solution_lists.find_best_guess();
-
-                // Find out if unconstrained vars have a better propability
-                let unconstrained_mines = (remaining_mines - solution_lists.min_mines()) as u32;
+                // No trivial solutions, guess the variable that is least
+                // likely to be a mine
                 let unconstrained_vars = self
                     .constraint_sets
                     .unconstrained_variables(&self.known_fields);
-                if !unconstrained_vars.is_empty() {
-                    let len = unconstrained_vars.iter().count() as u32;
-                    assert!(len > 0);
-                    let non_mines = len - unconstrained_mines.min(len);
-                    let propability = non_mines as f32 / len as f32;
-                    if propability > best_guess.1 {
-                        best_guess = (guess(unconstrained_vars), propability);
+                if let Some(decision) = self.endgame_guess(
+                    &solution_lists,
+                    &unconstrained_vars,
+                    remaining_mines,
+                    minefield,
+                ) {
+                    return vec![decision];
+                }
+                let Some(probabilities) =
+                    MineProbabilities::from(&solution_lists, unconstrained_vars, remaining_mines)
+                else {
+                    let (coord, probability) = solution_lists.find_best_guess();
+                    return vec![Decision::GuessReveal(
+                        coord,
+                        FixedU32::from_num(probability),
+                    )];
+                };
+
+                if self.guess_strategy == GuessStrategy::Scored {
+                    if let Some(score) = guess_scoring::best_guess(
+                        &probabilities,
+                        &solution_lists,
+                        &self.known_fields,
+                        minefield,
+                    ) {
+                        return vec![Decision::GuessReveal(
+                            score.coord,
+                            FixedU32::from_num(score.safety),
+                        )];
                     }
                 }
 
-                // Pick which one was better
+                let mut best_guess = probabilities.safest_constrained();
+                if !unconstrained_vars.is_empty()
+                    && !matches!(best_guess, Some((_, p)) if p <= probabilities.unconstrained_probability)
+                {
+                    best_guess = Some((
+                        guess(unconstrained_vars, minefield),
+                        probabilities.unconstrained_probability,
+                    ));
+                }
+
+                let (coord, probability) = best_guess.unwrap();
                 vec![Decision::GuessReveal(
-                    best_guess.0,
-                    FixedU32::from_num(best_guess.1),
+                    coord,
+                    FixedU32::from_num(1. - probability),
                 )]
             }
         } else {
@@ -204,25 +343,85 @@ impl<const W: usize, const H: usize> CSPState<W, H> {
             let vars = self
                 .constraint_sets
                 .unconstrained_variables(&self.known_fields);
+            if let Some(decision) = self.endgame_guess(&[], &vars, remaining_mines, minefield) {
+                return vec![decision];
+            }
             let len = vars.iter().count();
             let propability = 1. - (remaining_mines as f32 / len as f32);
             vec![Decision::GuessReveal(
-                guess(vars),
+                guess(vars, minefield),
                 FixedU32::from_num(propability),
             )]
         }
     }
+
+    /// Guess the cell that is the most likely to win the game, if there are at
+    /// most [CSPState::endgame_size] hidden cells left, see [endgame].
+    fn endgame_guess(
+        &self,
+        solution_lists: &[SolutionList<W, H>],
+        unconstrained_vars: &CoordSet<W, H>,
+        remaining_mines: u16,
+        minefield: &Minefield<W, H>,
+    ) -> Option<Decision<W, H>> {
+        let endgame_move = endgame::best_move(
+            solution_lists,
+            unconstrained_vars,
+            remaining_mines,
+            &self.known_fields,
+            minefield,
+            self.endgame_size,
+        )?;
+        Some(Decision::GuessReveal(
+            endgame_move.coord,
+            FixedU32::from_num(endgame_move.safety),
+        ))
+    }
+
+    /// Calculate the probability of every cell being a mine in the current
+    /// position, see [probabilities]. Cells that are already known are either
+    /// 0 or 1, so revealed cells are always 0. Does not change the state in
+    /// any way. Returns None if the remaining mines can not be placed in any
+    /// way that fits the current state.
+    pub fn mine_probabilities(&self, minefield: &Minefield<W, H>) -> Option<Matrix<f32, W, H>> {
+        let remaining_mines = self.remaining_mines(minefield);
+        let solution_lists = self
+            .constraint_sets
+            .find_viable_solutions(remaining_mines, &self.known_fields);
+        let unconstrained_vars = self
+            .constraint_sets
+            .unconstrained_variables(&self.known_fields);
+        let probabilities =
+            MineProbabilities::from(&solution_lists, unconstrained_vars, remaining_mines)?;
+        Some(probabilities.matrix(&self.known_fields))
+    }
+
+    /// The amount of mines in the minefield that are not known yet.
+    fn remaining_mines(&self, minefield: &Minefield<W, H>) -> u16 {
+        let found_mines = self
+            .known_fields
+            .iter()
+            .flatten()
+            .filter(|c| **c == CellContent::Known(true))
+            .count() as u16;
+        minefield.mines - found_mines
+    }
 }
 
 /// Make a purely random guess. At least for now, this function is meant for use
-/// simply so that the game will never stagnate entirely.
+/// simply so that the game will never stagnate entirely. Corners and edges are
+/// those of the actual size of the minefield.
 ///
 /// Still not very good, but at least it's trying
-pub fn guess<const W: usize, const H: usize>(mut available_vars: CoordSet<W, H>) -> Coord<W, H> {
+pub fn guess<const W: usize, const H: usize>(
+    mut available_vars: CoordSet<W, H>,
+    minefield: &Minefield<W, H>,
+) -> Coord<W, H> {
     let mut rng = rand::thread_rng();
 
-    let corners = CoordSet::corners().intersection(&available_vars);
-    let edges = CoordSet::edges().intersection(&available_vars);
+    let (width, height) = (minefield.width(), minefield.height());
+    let corners = CoordSet::corners(width, height).intersection(&available_vars);
+    let edges = CoordSet::edges(width, height).intersection(&available_vars);
     available_vars.omit(&corners);
     available_vars.omit(&edges);
 
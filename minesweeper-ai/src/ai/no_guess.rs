@@ -0,0 +1,78 @@
+//! Contains a generator for minefields that [CSPState] is able to clear from a
+//! fixed starting tile without ever having to guess, meaning that it never
+//! returns a [Decision::GuessReveal]. These minefields are nicer for humans to
+//! play, and measure the speed of pure deduction without any luck involved.
+
+use miinaharava::minefield::{Coord, GameState, Minefield};
+
+use super::{CSPState, Decision};
+
+/// A minefield that can be cleared without guessing, see
+/// [crate::ai::no_guess].
+#[derive(Debug, Clone)]
+pub struct NoGuessMinefield<const W: usize, const H: usize> {
+    /// The minefield with nothing revealed yet. Depending on the policy of the
+    /// minefield the mines may only be placed on the first reveal, so `start`
+    /// must be revealed first.
+    pub minefield: Minefield<W, H>,
+    /// The tile that has to be revealed first, which is the center of the
+    /// minefield.
+    pub start: Coord<W, H>,
+    /// How many candidate minefields were generated, including the accepted
+    /// one.
+    pub candidates: u32,
+}
+
+/// Generate candidate minefields with the given function until one of them can
+/// be cleared without guessing when started from the center, or until
+/// `max_candidates` minefields have been rejected. Rejected minefields are
+/// simply thrown away.
+pub fn generate_no_guess<const W: usize, const H: usize>(
+    mut candidate: impl FnMut() -> Minefield<W, H>,
+    max_candidates: u32,
+) -> Option<NoGuessMinefield<W, H>> {
+    for candidates in 1..=max_candidates {
+        let minefield = candidate();
+        let start = Coord(minefield.width() / 2, minefield.height() / 2);
+        if solves_without_guessing(&minefield, start) {
+            return Some(NoGuessMinefield {
+                minefield,
+                start,
+                candidates,
+            });
+        }
+    }
+    None
+}
+
+/// Whether [CSPState] clears the minefield from the given starting tile
+/// without ever guessing. The given minefield itself is left untouched.
+pub fn solves_without_guessing<const W: usize, const H: usize>(
+    minefield: &Minefield<W, H>,
+    start: Coord<W, H>,
+) -> bool {
+    let mut minefield = minefield.clone();
+    let Ok(mut reveals) = minefield.reveal(start) else {
+        return false;
+    };
+    let mut state = CSPState::new(&minefield);
+
+    while minefield.game_state() == GameState::Pending {
+        let decisions = state.ponder(std::mem::take(&mut reveals), &minefield);
+        if decisions.is_empty() {
+            return false;
+        }
+        for decision in decisions {
+            let res = match decision {
+                Decision::Reveal(coord) => minefield.reveal(coord),
+                Decision::Flag(coord) => minefield.flag(coord),
+                Decision::Chord(coord) => minefield.chord(coord),
+                Decision::GuessReveal(..) => return false,
+            };
+            if let Ok(res) = res {
+                reveals.extend(res);
+            }
+        }
+    }
+    minefield.game_state() == GameState::Victory
+}
@@ -0,0 +1,218 @@
+//! This module calculates the exact probability of every hidden cell being a
+//! mine, represented by [MineProbabilities].
+//!
+//! Every solution of every [ConstraintSet](crate::ai::constraint_sets::ConstraintSet)
+//! is not equally likely. A combination of solutions, one from every set, that
+//! uses M mines in total leaves R - M of the R remaining mines for the U
+//! unconstrained variables, which can be done in C(U, R - M) ways. So every
+//! combination of solutions is weighted by this binomial coefficient, and the
+//! probability of a variable being a mine is the total weight of the
+//! combinations where it is a mine divided by the total weight of all of the
+//! combinations.
+//!
+//! Instead of going through every combination, the solutions of every set are
+//! counted by their amount of mines, and these counts are convolved together
+//! so that the weight of a single solution only depends on how many mines
+//! every other set has in total.
+
+use miinaharava::minefield::{Coord, Matrix};
+
+use super::{
+    backtracking::solutions::SolutionList, coord_set::CoordSet, CellContent, KnownMinefield,
+};
+
+/// The exact probabilities of every hidden cell being a mine, see
+/// [crate::ai::probabilities].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MineProbabilities<const W: usize, const H: usize> {
+    /// The probability of being a mine for every constrained variable
+    pub constrained: Vec<(Coord<W, H>, f64)>,
+    /// All of the variables that are not in any constraint
+    pub unconstrained: CoordSet<W, H>,
+    /// The probability of being a mine for every unconstrained variable
+    pub unconstrained_probability: f64,
+    /// The (scaled) weight of a single solution of every set of solutions, by
+    /// the amount of mines in the solution
+    pub solution_weights: Vec<Vec<f64>>,
+}
+
+impl<const W: usize, const H: usize> MineProbabilities<W, H> {
+    /// Calculate the probabilities from the solutions of every constraint set,
+    /// the unconstrained variables and the amount of remaining mines. Returns
+    /// None if no combination of the solutions is possible with the remaining
+    /// mines.
+    pub fn from(
+        solution_lists: &[SolutionList<W, H>],
+        unconstrained: CoordSet<W, H>,
+        remaining_mines: u16,
+    ) -> Option<MineProbabilities<W, H>> {
+        let unconstrained_count = unconstrained.iter().count();
+        let counts = solution_lists
+            .iter()
+            .map(|list| normalized(mine_counts(list)))
+            .collect::<Vec<_>>();
+
+        // Mine counts of all of the sets before and after every set
+        let mut before = vec![vec![1.]];
+        for count in &counts {
+            before.push(convolve(before.last().unwrap(), count));
+        }
+        let mut after = vec![vec![1.]];
+        for count in counts.iter().rev() {
+            after.push(convolve(count, after.last().unwrap()));
+        }
+        after.reverse();
+
+        let all = &before[counts.len()];
+        let weights = binomial_weights(all.len(), unconstrained_count, remaining_mines);
+
+        let mut constrained = Vec::new();
+        let mut all_solution_weights = Vec::with_capacity(solution_lists.len());
+        for (i, list) in solution_lists.iter().enumerate() {
+            // Weight of a solution of this set by its amount of mines
+            let others = convolve(&before[i], &after[i + 1]);
+            let solution_weights = (0..list.solutions_by_mines.len())
+                .map(|mines| {
+                    others
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(other_mines, count)| {
+                            weights.get(mines + other_mines).map(|w| count * w)
+                        })
+                        .sum::<f64>()
+                })
+                .collect::<Vec<_>>();
+
+            let mut total = 0.;
+            let mut mine_weights = vec![0.; list.coords.len()];
+            for (mines, solutions) in list.solutions_by_mines.iter().enumerate() {
+                for solution in solutions {
+                    total += solution_weights[mines];
+                    for idx in solution.iter_ones() {
+                        mine_weights[idx] += solution_weights[mines];
+                    }
+                }
+            }
+            if total <= 0. {
+                return None;
+            }
+            constrained.extend(
+                list.coords
+                    .iter()
+                    .zip(mine_weights)
+                    .map(|(coord, weight)| (*coord, weight / total)),
+            );
+            all_solution_weights.push(solution_weights);
+        }
+
+        let mut total = 0.;
+        let mut unconstrained_mines = 0.;
+        for (mines, (count, weight)) in all.iter().zip(&weights).enumerate() {
+            total += count * weight;
+            unconstrained_mines +=
+                count * weight * remaining_mines.saturating_sub(mines as u16) as f64;
+        }
+        if total <= 0. {
+            return None;
+        }
+        let unconstrained_probability = match unconstrained_count {
+            0 => 0.,
+            len => unconstrained_mines / total / len as f64,
+        };
+
+        Some(MineProbabilities {
+            constrained,
+            unconstrained,
+            unconstrained_probability,
+            solution_weights: all_solution_weights,
+        })
+    }
+
+    /// Return the constrained variable that is least likely to be a mine, along
+    /// with the probability of it being a mine.
+    pub fn safest_constrained(&self) -> Option<(Coord<W, H>, f64)> {
+        self.constrained
+            .iter()
+            .copied()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Return the probability of being a mine for every cell, where the cells
+    /// that are neither constrained nor unconstrained variables are either 0
+    /// or 1 depending on what they are known to be.
+    pub fn matrix(&self, known_fields: &KnownMinefield<W, H>) -> Matrix<f32, W, H> {
+        let mut matrix = Matrix::from(0.);
+        for (y, row) in known_fields.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell == CellContent::Known(true) {
+                    matrix.set(Coord(x as u16, y as u16), 1.);
+                }
+            }
+        }
+        for coord in self.unconstrained.iter() {
+            matrix.set(coord, self.unconstrained_probability as f32);
+        }
+        for (coord, probability) in &self.constrained {
+            matrix.set(*coord, *probability as f32);
+        }
+        matrix
+    }
+}
+
+/// Count the solutions of a list by their amount of mines, where the index is
+/// the amount of mines.
+fn mine_counts<const W: usize, const H: usize>(list: &SolutionList<W, H>) -> Vec<f64> {
+    list.solutions_by_mines
+        .iter()
+        .map(|solutions| solutions.len() as f64)
+        .collect()
+}
+
+/// Scale the values so that the largest one is 1. Only the ratios of the
+/// counts matter, and this keeps their products from overflowing.
+fn normalized(mut values: Vec<f64>) -> Vec<f64> {
+    let max = values.iter().copied().fold(0., f64::max);
+    if max > 0. {
+        for value in &mut values {
+            *value /= max;
+        }
+    }
+    values
+}
+
+/// Convolve two mine counts, so that the result tells the (scaled) amount of
+/// combinations for every total amount of mines.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    normalized(result)
+}
+
+/// Return the (scaled) amount of ways the rest of the remaining mines can be
+/// placed in the unconstrained variables, when the constrained variables have
+/// 0..len mines. The coefficients are calculated as logarithms, as they could
+/// easily overflow otherwise.
+fn binomial_weights(len: usize, unconstrained: usize, remaining_mines: u16) -> Vec<f64> {
+    let mut log_binomials = Vec::with_capacity(unconstrained + 1);
+    let mut log_binomial = 0.;
+    for k in 0..=unconstrained {
+        log_binomials.push(log_binomial);
+        log_binomial += ((unconstrained - k) as f64).ln() - ((k + 1) as f64).ln();
+    }
+
+    let logs = (0..len)
+        .map(|mines| {
+            (remaining_mines as usize)
+                .checked_sub(mines)
+                .and_then(|k| log_binomials.get(k).copied())
+        })
+        .collect::<Vec<_>>();
+    let max = logs.iter().flatten().copied().fold(f64::MIN, f64::max);
+    logs.into_iter()
+        .map(|log| log.map_or(0., |log| (log - max).exp()))
+        .collect()
+}
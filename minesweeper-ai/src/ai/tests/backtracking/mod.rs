@@ -57,7 +57,7 @@ fn find_solutions_finds_only_valid_solutions_and_correct_solutions() {
         // Generate valid constraints
         let (set, mine_coords) = get_fast_valid_constraints();
 
-        let known = Matrix([[CellContent::Unknown; 10]; 10]);
+        let mut known = Matrix([[CellContent::Unknown; 10]; 10]);
         // Only take the variables that are in more than 1 constraint, otherwise
         // tests are slowed down a LOT!
         let ordered = set.find_ordered();
@@ -68,7 +68,7 @@ fn find_solutions_finds_only_valid_solutions_and_correct_solutions() {
             correct_solution.push(mine_coords.contains(coord));
         }
 
-        let possible_solutions = set.find_solutions(&ordered, BitVec::new(), known);
+        let possible_solutions = set.find_solutions(&ordered, BitVec::new(), &mut known);
         dbg!(&possible_solutions);
 
         // 1. Make sure correct solution is found
@@ -88,6 +88,9 @@ fn find_solutions_finds_only_valid_solutions_and_correct_solutions() {
                 assert_eq!(set.constraints[i].label, *constraint);
             }
         }
+
+        // 3. Make sure the known field is left as it was
+        assert_eq!(known, Matrix([[CellContent::Unknown; 10]; 10]));
     }
 }
 
@@ -98,7 +101,7 @@ fn test_constraint_sets_find_viable_solutions() {
         let (set, mine_coords) = get_fast_valid_constraints();
         let known = Matrix([[CellContent::Unknown; 10]; 10]);
 
-        let mut solution_list_map = set.find_viable_solutions(mine_coords.len() as u8, &known);
+        let mut solution_list_map = set.find_viable_solutions(mine_coords.len() as u16, &known);
 
         // Ensure that no duplicate solutions exist
         for solution_list in solution_list_map.solutions_by_mines.iter() {
@@ -124,11 +127,11 @@ fn test_constraint_sets_find_viable_solutions() {
         for i in ((min_mines as i32 - 10).max(0) as usize)..(max_mines + 10) {
             dbg!(i);
             assert_eq!(
-                solution_list_map.get(i as u8).is_some(),
+                solution_list_map.get(i as u16).is_some(),
                 i >= min_mines && i <= max_mines
             );
             assert_eq!(
-                solution_list_map.get_mut(i as u8).is_some(),
+                solution_list_map.get_mut(i as u16).is_some(),
                 i >= min_mines && i <= max_mines
             );
         }
@@ -151,14 +154,14 @@ fn test_coupled_set_find_viable_solutions() {
             .next()
             .unwrap()[0]
             .iter_ones()
-            .count() as u8;
+            .count() as u16;
         let mine_count2 = set2
             .find_viable_solutions(20, &known)
             .iter()
             .next()
             .unwrap()[0]
             .iter_ones()
-            .count() as u8;
+            .count() as u16;
         let min_mines = mine_count1 + mine_count2;
 
         // Get the amount of mines actually revealed through constraints
@@ -170,7 +173,7 @@ fn test_coupled_set_find_viable_solutions() {
             .iter()
             .chain(set2.variables.iter())
             .filter(|v| mine_coords.contains(&v))
-            .count() as u8;
+            .count() as u16;
 
         let sets = CoupledSets(vec![set1, set2]);
 
@@ -45,7 +45,7 @@ fn test_transposed_solution_coord() {
 
         let solution_list = SolutionList::from(solutions.clone(), coords.clone(), 100);
 
-        assert_eq!(solution_list.get(mine_amount).unwrap(), &solutions);
+        assert_eq!(solution_list.get(mine_amount as u16).unwrap(), &solutions);
 
         let mut transposed: Vec<(Coord<7, 7>, BitVec)> = Vec::new();
         for (idx, coord) in coords.iter().enumerate() {
@@ -211,8 +211,8 @@ fn test_solution_list_min_max() {
         }
 
         let solution_list = SolutionList::from(solutions.clone(), coords.clone(), 100);
-        assert_eq!(solution_list.min_mines(), actual_min);
-        assert_eq!(solution_list.max_mines(), actual_max);
+        assert_eq!(solution_list.min_mines(), actual_min as u16);
+        assert_eq!(solution_list.max_mines(), actual_max as u16);
     }
 }
 
@@ -279,20 +279,12 @@ fn test_find_best_guess() {
     }
 }
 
-struct BestGuessMock<const W: usize, const H: usize>(Coord<W, H>, f32, u8, u8);
+struct BestGuessMock<const W: usize, const H: usize>(Coord<W, H>, f32);
 
 impl<const W: usize, const H: usize> SolutionContainer<W, H> for BestGuessMock<W, H> {
     fn find_best_guess(&self) -> (Coord<W, H>, f32) {
         (self.0, self.1)
     }
-
-    fn max_mines(&self) -> u8 {
-        self.3
-    }
-
-    fn min_mines(&self) -> u8 {
-        self.2
-    }
 }
 
 /// Ensure that the best guess is always found correctly, at least if the best
@@ -314,12 +306,7 @@ fn test_find_best_guess_from_vec() {
         let best_guess_amount = black_box(rand::random::<u8>() % 50 + 50);
         let best_guess_coord = random_coords.pop().unwrap();
         let best_guess_propability = best_guess_amount as f32 / hypothetical_max;
-        mock_guesses.push(BestGuessMock(
-            best_guess_coord,
-            best_guess_propability,
-            10,
-            10,
-        ));
+        mock_guesses.push(BestGuessMock(best_guess_coord, best_guess_propability));
 
         let non_best_guess_max = best_guess_amount - 25;
 
@@ -328,8 +315,6 @@ fn test_find_best_guess_from_vec() {
             mock_guesses.push(BestGuessMock(
                 random_coords.pop().unwrap(),
                 random_amount as f32 / hypothetical_max,
-                10,
-                10,
             ));
         }
 
@@ -339,50 +324,3 @@ fn test_find_best_guess_from_vec() {
         assert_eq!(best_guess, (best_guess_coord, best_guess_propability));
     }
 }
-
-/// Ensure that the best guess is always found correctly, at least if the best
-/// guess is trivial.
-#[test]
-fn test_mines_min_max_from_vec() {
-    let mut rng = rand::thread_rng();
-
-    for _ in 0..5000 {
-        // Get guaranteedly the coord_amount of coords.
-        let mut random_coords = CoordSet::<7, 7>::from(true).iter().collect::<Vec<_>>();
-        random_coords.shuffle(&mut rng);
-
-        let hypothetical_max = 100.;
-
-        let max_mines = black_box(rand::random::<u8>() % 5 + 5);
-        let min_mines = black_box(rand::random::<u8>() % (max_mines - 4) + 4);
-
-        dbg!(min_mines);
-        dbg!(max_mines);
-
-        let solution_amount = black_box(rand::random::<u8>() % 10 + 10);
-        let mut mock_guesses = Vec::new();
-
-        let mut actual_min_mines = 0;
-        let mut actual_max_mines = 0;
-
-        for _ in 0..solution_amount {
-            let random_amount = black_box(rand::random::<u8>() % 100);
-            let max = rng.gen_range(min_mines..=max_mines);
-            let min = rng.gen_range(min_mines..=max);
-            dbg!(min, max);
-            actual_min_mines += min;
-            actual_max_mines += max;
-            mock_guesses.push(BestGuessMock(
-                random_coords.pop().unwrap(),
-                random_amount as f32 / hypothetical_max,
-                min,
-                max,
-            ));
-        }
-
-        mock_guesses.shuffle(&mut rng);
-
-        assert_eq!(mock_guesses.min_mines(), actual_min_mines);
-        assert_eq!(mock_guesses.max_mines(), actual_max_mines);
-    }
-}
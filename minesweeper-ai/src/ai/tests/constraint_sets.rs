@@ -222,7 +222,7 @@ fn test_trivial_solver_on_trivial() {
             //    cells were touched.
             for (y, row) in known.iter().enumerate() {
                 for (x, cell) in row.iter().enumerate() {
-                    if variables.contains(&Coord(x as u8, y as u8)) {
+                    if variables.contains(&Coord(x as u16, y as u16)) {
                         assert_eq!(*cell, CellContent::Known(multiplier == 1));
                     } else {
                         assert_eq!(*cell, CellContent::Unknown)
@@ -392,6 +392,7 @@ fn test_trivial_solver_with_known_variables() {
                     assert!(!true_variables.contains(c));
                     assert_eq!(known.get(*c), CellContent::Known(mine_coords.contains(c)));
                 }
+                Decision::Chord(_) => panic!("trivial solving should never chord"),
             }
         }
 
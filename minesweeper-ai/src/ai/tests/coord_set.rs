@@ -22,7 +22,7 @@ fn test_corners() {
         ]),
     };
 
-    assert_eq!(CoordSet::corners(), expected);
+    assert_eq!(CoordSet::corners(5, 5), expected);
 }
 
 #[test]
@@ -37,7 +37,30 @@ fn test_edges() {
         ]),
     };
 
-    assert_eq!(CoordSet::edges(), expected);
+    assert_eq!(CoordSet::edges(5, 5), expected);
+}
+
+#[test]
+fn test_corners_and_edges_of_smaller_area() {
+    let corners = CoordSet {
+        matrix: Matrix([
+            [true, false, false, true, false],
+            [false; 5],
+            [true, false, false, true, false],
+            [false; 5],
+        ]),
+    };
+    let edges = CoordSet {
+        matrix: Matrix([
+            [false, true, true, false, false],
+            [true, false, false, true, false],
+            [false, true, true, false, false],
+            [false; 5],
+        ]),
+    };
+
+    assert_eq!(CoordSet::corners(4, 3), corners);
+    assert_eq!(CoordSet::edges(4, 3), edges);
 }
 
 #[test]
@@ -53,8 +76,8 @@ fn test_middle_with_omit() {
     };
 
     let mut actual = CoordSet::from(true);
-    actual.omit(&CoordSet::edges());
-    actual.omit(&CoordSet::corners());
+    actual.omit(&CoordSet::edges(5, 5));
+    actual.omit(&CoordSet::corners(5, 5));
 
     assert_eq!(actual, expected);
 }
@@ -151,7 +174,7 @@ fn test_contains() {
 
         for (y, row) in clone.matrix.0.iter_mut().enumerate() {
             for (x, cell) in row.iter_mut().enumerate() {
-                *cell = set.contains(Coord(x as u8, y as u8));
+                *cell = set.contains(Coord(x as u16, y as u16));
             }
         }
 
@@ -167,7 +190,7 @@ fn test_iter() {
 
         for y in 0..30 {
             for x in 0..30 {
-                let c = Coord(x as u8, y as u8);
+                let c = Coord(x as u16, y as u16);
                 if set.contains(c) {
                     definitely_contained.push(c);
                 }
@@ -0,0 +1,90 @@
+use bitvec::prelude::*;
+use miinaharava::minefield::{Coord, Matrix, Minefield};
+
+use crate::ai::{
+    backtracking::solutions::SolutionList, coord_set::CoordSet, endgame::best_move, CellContent,
+};
+
+/// Return a known minefield where only the given cells are unknown.
+fn known_except(cells: &[Coord<7, 7>]) -> Matrix<CellContent, 7, 7> {
+    let mut known = Matrix::from(CellContent::Known(false));
+    for cell in cells {
+        known.set(*cell, CellContent::Unknown);
+    }
+    known
+}
+
+/// A single mine in one of two cells far away from each other, so nothing can
+/// be learned and the game is won half of the time.
+#[test]
+fn test_fifty_fifty() {
+    let minefield = Minefield::<7, 7>::with_mines(Matrix::from(false));
+    let cells = [Coord(1, 1), Coord(5, 5)];
+    let lists = [SolutionList::from(
+        vec![bitvec![1, 0], bitvec![0, 1]],
+        cells.to_vec(),
+        1,
+    )];
+    let known = known_except(&cells);
+
+    let best = best_move(&lists, &CoordSet::default(), 1, &known, &minefield, 16).unwrap();
+    assert!(cells.contains(&best.coord));
+    assert!((best.safety - 0.5).abs() < 1e-9);
+    assert!((best.win_probability - 0.5).abs() < 1e-9);
+
+    // Too many hidden cells to search
+    assert_eq!(
+        best_move(&lists, &CoordSet::default(), 1, &known, &minefield, 1),
+        None
+    );
+}
+
+/// A single mine in one of three equally safe cells, where only the label of
+/// the two cells next to each other can tell where the mine is. The cell
+/// further away has no hidden neighbours, so revealing it would leave a 50/50.
+#[test]
+fn test_informative_reveal_wins() {
+    let minefield = Minefield::<7, 7>::with_mines(Matrix::from(false));
+    let cells = [Coord(2, 3), Coord(3, 3), Coord(5, 3)];
+    let mut unconstrained = CoordSet::default();
+    unconstrained.insert_many(cells.iter().copied());
+    let known = known_except(&cells);
+
+    let best = best_move(&[], &unconstrained, 1, &known, &minefield, 16).unwrap();
+    assert!(best.coord == Coord(2, 3) || best.coord == Coord(3, 3));
+    assert!((best.safety - 2. / 3.).abs() < 1e-9);
+    assert!((best.win_probability - 2. / 3.).abs() < 1e-9);
+}
+
+/// Revealing the safest cell is not always best. (2, 4) and (4, 4) are safe
+/// in 3 of the 4 layouts, but they have no hidden neighbours, so revealing
+/// them tells nothing and leaves three equally likely layouts. (2, 2) is safe
+/// in only half of the layouts, but when it is, its label tells whether
+/// (3, 2) is a mine, which tells the layout.
+#[test]
+fn test_riskier_reveal_wins() {
+    let minefield = Minefield::<7, 7>::with_mines(Matrix::from(false));
+    let cells = vec![
+        Coord(2, 2),
+        Coord(2, 4),
+        Coord(4, 2),
+        Coord(4, 4),
+        Coord(3, 2),
+    ];
+    let lists = [SolutionList::from(
+        vec![
+            bitvec![1, 0, 1, 0, 0],
+            bitvec![0, 1, 0, 1, 0],
+            bitvec![1, 0, 0, 0, 1],
+            bitvec![0, 0, 1, 0, 1],
+        ],
+        cells.clone(),
+        2,
+    )];
+    let known = known_except(&cells);
+
+    let best = best_move(&lists, &CoordSet::default(), 2, &known, &minefield, 16).unwrap();
+    assert_eq!(best.coord, Coord(2, 2));
+    assert!((best.safety - 0.5).abs() < 1e-9);
+    assert!((best.win_probability - 0.5).abs() < 1e-9);
+}
@@ -0,0 +1,67 @@
+use bitvec::prelude::*;
+use miinaharava::minefield::{Coord, Matrix, Minefield};
+
+use crate::ai::{
+    backtracking::solutions::SolutionList,
+    coord_set::CoordSet,
+    guess_scoring::{best_guess, score_guesses, GAIN_WEIGHT},
+    probabilities::MineProbabilities,
+    CellContent,
+};
+
+/// Every guess on an empty minefield is as safe as any other, so the best
+/// guess is the one that is most likely to be an opening, which is a corner.
+#[test]
+fn best_first_guess_is_a_corner() {
+    let minefield = Minefield::<7, 7>::with_mines(Matrix::from(false));
+    let probabilities = MineProbabilities {
+        constrained: Vec::new(),
+        unconstrained: CoordSet::from(true),
+        unconstrained_probability: 0.2,
+        solution_weights: Vec::new(),
+    };
+    let known = Matrix::from(CellContent::Unknown);
+
+    let scores = score_guesses(&probabilities, &[], &known, &minefield);
+    assert_eq!(scores.len(), 49);
+    let best = best_guess(&probabilities, &[], &known, &minefield).unwrap();
+    assert!(matches!(best.coord, Coord(0 | 6, 0 | 6)));
+    assert!((best.opening - 0.8f64.powi(3)).abs() < 1e-6);
+}
+
+/// Two constraints `1` over (0, 3), (1, 3) and (1, 3), (2, 3), so either the
+/// middle one is a mine or both of the others are. If (0, 3) is safe, (2, 3)
+/// is certainly safe as well.
+#[test]
+fn test_next_safety() {
+    let minefield = Minefield::<7, 7>::with_mines(Matrix::from(false));
+    let lists = [SolutionList::from(
+        vec![bitvec![0, 1, 0], bitvec![1, 0, 1]],
+        vec![Coord(0, 3), Coord(1, 3), Coord(2, 3)],
+        3,
+    )];
+    let mut unconstrained = CoordSet::default();
+    for x in 0..7 {
+        unconstrained.insert(Coord(x, 6));
+    }
+    let mut known = Matrix::from(CellContent::Known(false));
+    for coord in lists[0].coords.iter().copied().chain(unconstrained.iter()) {
+        known.set(coord, CellContent::Unknown);
+    }
+    let probabilities = MineProbabilities::from(&lists, unconstrained, 3).unwrap();
+
+    let scores = score_guesses(&probabilities, &lists, &known, &minefield);
+    let score = scores.iter().find(|s| s.coord == Coord(0, 3)).unwrap();
+    assert!((score.safety - 0.75).abs() < 1e-6);
+    assert!((score.next_safety - 1.).abs() < 1e-6);
+    assert_eq!(score.sets, 1);
+
+    // The middle one is only a quarter safe, so it is not worth scoring
+    assert!(scores.iter().all(|s| s.coord != Coord(1, 3)));
+
+    // Gains never make up for being much less safe
+    let safest = scores.iter().map(|s| s.safety).fold(0., f64::max);
+    let best = best_guess(&probabilities, &lists, &known, &minefield).unwrap();
+    assert!(best.safety * (1. + GAIN_WEIGHT) >= safest);
+    assert!(scores.iter().all(|s| s.score() <= best.score()));
+}
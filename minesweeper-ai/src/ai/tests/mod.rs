@@ -1,7 +1,9 @@
 use std::hint::black_box;
 
 use arrayvec::ArrayVec;
-use miinaharava::minefield::{Coord, GameState, Matrix, Minefield};
+use miinaharava::minefield::{
+    Cell, Coord, GameState, GenerationPolicy, Matrix, Minefield, Reveal, Topology,
+};
 use rand::{seq::SliceRandom, Rng};
 
 use crate::ai::{
@@ -12,6 +14,10 @@ use crate::ai::{
 mod backtracking;
 mod constraint_sets;
 mod coord_set;
+mod endgame;
+mod guess_scoring;
+mod no_guess;
+mod probabilities;
 
 pub const TRIVIAL_MINES: Matrix<bool, 7, 7> = Matrix([
     [false, false, false, false, false, false, false],
@@ -23,31 +29,249 @@ pub const TRIVIAL_MINES: Matrix<bool, 7, 7> = Matrix([
     [false, true, false, true, false, false, false],
 ]);
 
-#[test]
-fn solve_trivial_field() {
-    let mut minefield = Minefield::<7, 7>::with_mines(TRIVIAL_MINES);
-    let mut state = CSPState::<7, 7>::default();
-
-    let mut reveals = minefield.reveal(Coord(0, 0)).unwrap();
-
-    let mut max_decisions = 20;
-    while minefield.game_state() == GameState::Pending && max_decisions > 0 {
-        let decisions = state.ponder(reveals.drain(..).collect(), &minefield);
+/// Let the AI play the minefield until the game ends, starting from the given
+/// reveals. `on_ponder` is called with the decisions of every ponder before
+/// they are done, and `on_decision` after every single decision is done.
+fn play<const W: usize, const H: usize>(
+    minefield: &mut Minefield<W, H>,
+    state: &mut CSPState<W, H>,
+    mut reveals: Vec<Reveal<W, H>>,
+    mut on_ponder: impl FnMut(&CSPState<W, H>, &Minefield<W, H>, &[Decision<W, H>]),
+    mut on_decision: impl FnMut(&Minefield<W, H>, Decision<W, H>),
+) {
+    while minefield.game_state() == GameState::Pending {
+        let decisions = state.ponder(std::mem::take(&mut reveals), minefield);
+        on_ponder(state, minefield, &decisions);
         for decision in decisions {
             if let Some(res) = match decision {
                 Decision::Flag(coord) => minefield.flag(coord).ok(),
+                Decision::Chord(coord) => minefield.chord(coord).ok(),
                 Decision::Reveal(coord) | Decision::GuessReveal(coord, _) => {
                     minefield.reveal(coord).ok()
                 }
             } {
                 reveals.extend(res);
             }
+            on_decision(minefield, decision);
         }
-        max_decisions -= 1;
     }
+}
+
+#[test]
+fn solve_trivial_field() {
+    let mut minefield = Minefield::<7, 7>::with_mines(TRIVIAL_MINES);
+    let mut state = CSPState::<7, 7>::default();
+
+    let reveals = minefield.reveal(Coord(0, 0)).unwrap();
+    let mut ponders = 0;
+    play(
+        &mut minefield,
+        &mut state,
+        reveals,
+        |_, _, _| {
+            ponders += 1;
+            assert!(ponders <= 20);
+        },
+        |_, _| {},
+    );
     assert_eq!(minefield.game_state(), GameState::Victory)
 }
 
+/// [TRIVIAL_MINES] as an MBF board
+const TRIVIAL_MBF: [u8; 16] = [7, 7, 0, 6, 5, 2, 0, 3, 3, 3, 6, 5, 1, 6, 3, 6];
+
+#[test]
+fn solve_mbf_board() {
+    let mut minefield = Minefield::<16, 16>::from_mbf(&TRIVIAL_MBF).unwrap();
+    assert_eq!(minefield.to_mbf(), Ok(TRIVIAL_MBF.to_vec()));
+    let mut state = CSPState::new(&minefield);
+
+    let reveals = minefield.reveal(Coord(0, 0)).unwrap();
+    play(&mut minefield, &mut state, reveals, |_, _, _| {}, |_, _| {});
+    assert_eq!(minefield.game_state(), GameState::Victory);
+}
+
+#[test]
+fn solve_smaller_minefield() {
+    for seed in 0..50 {
+        let mut minefield = Minefield::<16, 16>::generate_sized(
+            12,
+            7,
+            10,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        let mut state = CSPState::new(&minefield);
+
+        play(
+            &mut minefield,
+            &mut state,
+            Vec::new(),
+            |_, minefield, decisions| {
+                for decision in decisions {
+                    let (Decision::Flag(coord)
+                    | Decision::Reveal(coord)
+                    | Decision::GuessReveal(coord, _)
+                    | Decision::Chord(coord)) = *decision;
+                    assert!(minefield.contains(coord));
+                }
+            },
+            |_, _| {},
+        );
+    }
+}
+
+/// Reveals that are not guesses should never lose.
+fn assert_safe_reveal<const W: usize, const H: usize>(
+    minefield: &Minefield<W, H>,
+    decision: Decision<W, H>,
+) {
+    if let Decision::Reveal(_) = decision {
+        assert_ne!(minefield.game_state(), GameState::GameOver);
+    }
+}
+
+#[test]
+fn solve_minefields_with_other_topologies() {
+    for topology in [
+        Topology::Toroidal,
+        Topology::Orthogonal,
+        Topology::Knight,
+        Topology::Hexagonal,
+    ] {
+        let mut victories = 0;
+        for seed in 0..20 {
+            let mut minefield = Minefield::<16, 16>::generate_sized(
+                12,
+                10,
+                15,
+                seed,
+                GenerationPolicy::OpeningFirstClick,
+            )
+            .unwrap();
+            minefield.set_topology(topology);
+            let mut state = CSPState::new(&minefield);
+
+            play(
+                &mut minefield,
+                &mut state,
+                Vec::new(),
+                |_, _, decisions| assert!(!decisions.is_empty()),
+                assert_safe_reveal,
+            );
+            if minefield.game_state() == GameState::Victory {
+                victories += 1;
+            }
+        }
+        assert!(victories > 0, "{:?}", topology);
+    }
+}
+
+#[test]
+fn solve_minefields_with_question_marks() {
+    for seed in 0..20 {
+        let mut minefield = Minefield::<16, 16>::generate_with_policy(
+            40,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        let reveals = minefield.reveal(Coord(8, 8)).unwrap();
+
+        // Mark every other hidden tile with a question mark, which the AI
+        // should treat exactly like a hidden tile
+        minefield.set_question_marks(true);
+        for y in 0..16 {
+            for x in (y % 2..16).step_by(2) {
+                if minefield.field.get(Coord(x, y)) == Cell::Hidden {
+                    minefield.flag(Coord(x, y)).unwrap();
+                    minefield.flag(Coord(x, y)).unwrap();
+                }
+            }
+        }
+        minefield.set_question_marks(false);
+
+        let mut state = CSPState::new(&minefield);
+        play(
+            &mut minefield,
+            &mut state,
+            reveals,
+            |_, _, decisions| assert!(!decisions.is_empty()),
+            |minefield, decision| {
+                if let Decision::Flag(coord) = decision {
+                    assert_eq!(minefield.field.get(coord), Cell::Flag);
+                }
+                assert_safe_reveal(minefield, decision);
+            },
+        );
+    }
+}
+
+#[test]
+fn solve_large_minefield() {
+    for seed in 0..3 {
+        let mut minefield = Minefield::<100, 100>::generate_with_policy(
+            2000,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        let mut state = CSPState::new(&minefield);
+
+        play(
+            &mut minefield,
+            &mut state,
+            Vec::new(),
+            |_, _, decisions| assert!(!decisions.is_empty()),
+            |_, _| {},
+        );
+    }
+}
+
+#[test]
+fn mine_probabilities_match_guesses() {
+    for seed in 0..20 {
+        let mut minefield = Minefield::<16, 16>::generate_with_policy(
+            40,
+            seed,
+            GenerationPolicy::OpeningFirstClick,
+        )
+        .unwrap();
+        let mut state = CSPState::new(&minefield);
+
+        play(
+            &mut minefield,
+            &mut state,
+            Vec::new(),
+            |state, minefield, decisions| {
+                let known_fields = state.known_fields;
+                let probabilities = state.mine_probabilities(minefield).unwrap();
+                assert_eq!(state.known_fields, known_fields);
+
+                // Revealed cells are never mines, and the probabilities add up
+                // to the amount of mines
+                for y in 0..16 {
+                    for x in 0..16 {
+                        let coord = Coord(x, y);
+                        if let Cell::Empty | Cell::Label(_) = minefield.field.get(coord) {
+                            assert_eq!(probabilities.get(coord), 0.);
+                        }
+                    }
+                }
+                let total = probabilities.iter().flatten().sum::<f32>();
+                assert!((total - 40.).abs() < 1e-3, "{}", total);
+
+                if let [Decision::GuessReveal(coord, probability)] = decisions[..] {
+                    let safe = 1. - probabilities.get(coord);
+                    assert!((safe - probability.to_num::<f32>()).abs() < 1e-3);
+                }
+            },
+            |_, _| {},
+        );
+    }
+}
+
 #[test]
 fn test_csp_insert() {
     for _ in 0..50 {
@@ -119,21 +343,49 @@ fn test_csp_ponder_with_reveals() {
     assert_eq!(*state.constraint_sets.0.get(0).unwrap(), expected_set);
 }
 
+#[test]
+fn test_reveals_are_chorded() {
+    let mut mines = Matrix([[false; 3]; 3]);
+    mines.set(Coord(0, 0), true);
+    let mut minefield = Minefield::<3, 3>::with_mines(mines);
+    minefield.reveal(Coord(1, 1)).unwrap();
+
+    let reveals = Coord::<3, 3>(1, 1)
+        .neighbours()
+        .into_iter()
+        .filter(|c| *c != Coord(0, 0))
+        .map(Decision::Reveal)
+        .collect::<Vec<_>>();
+
+    // Mine is not flagged yet, so chording is not possible
+    let decisions = CSPState::chord_reveals(reveals.clone(), &minefield);
+    assert_eq!(decisions, reveals);
+
+    minefield.flag(Coord(0, 0)).unwrap();
+    let decisions = CSPState::chord_reveals(reveals, &minefield);
+    assert_eq!(decisions, vec![Decision::Chord(Coord(1, 1))]);
+
+    minefield.chord(Coord(1, 1)).unwrap();
+    assert_eq!(minefield.game_state(), GameState::Victory);
+}
+
 #[test]
 fn first_guess_is_a_corner() {
+    let minefield = Minefield::generate(10).unwrap();
     for _ in 0..1000 {
-        let decision = guess(CoordSet::<10, 10>::from(true));
+        let decision = guess(CoordSet::<10, 10>::from(true), &minefield);
         assert!(matches!(decision, Coord(0 | 9, 0 | 9)));
     }
 }
 
 #[test]
 fn guess_without_corner_is_edge() {
+    let minefield = Minefield::generate(10).unwrap();
     for _ in 0..1000 {
         let mut available = CoordSet::<10, 10>::from(true);
-        available.omit(&CoordSet::corners());
+        available.omit(&CoordSet::corners(10, 10));
 
-        let decision = guess(available);
+        let decision = guess(available, &minefield);
         assert!(
             matches!(decision, Coord(x, 0 | 9) if x > 0 && x < 9)
                 || matches!(decision, Coord(0 | 9, y) if y > 0 && y < 9)
@@ -143,16 +395,33 @@ fn guess_without_corner_is_edge() {
 
 #[test]
 fn guess_without_corners_or_edges_is_middle() {
+    let minefield = Minefield::generate(10).unwrap();
     for _ in 0..1000 {
         let mut available = CoordSet::<10, 10>::from(true);
-        available.omit(&CoordSet::corners());
-        available.omit(&CoordSet::edges());
+        available.omit(&CoordSet::corners(10, 10));
+        available.omit(&CoordSet::edges(10, 10));
 
-        let decision = guess(available);
+        let decision = guess(available, &minefield);
         assert!(matches!(decision, Coord(x, y) if x > 0 && x < 9 && y > 0 && y < 9));
     }
 }
 
+#[test]
+fn guess_on_smaller_minefield_is_its_corner() {
+    let minefield =
+        Minefield::<10, 10>::generate_sized(6, 4, 5, 0, GenerationPolicy::Random).unwrap();
+    let state = CSPState::new(&minefield);
+    for _ in 0..1000 {
+        let available = state
+            .constraint_sets
+            .unconstrained_variables(&state.known_fields);
+        assert_eq!(available.iter().count(), 6 * 4);
+
+        let decision = guess(available, &minefield);
+        assert!(matches!(decision, Coord(0 | 5, 0 | 3)));
+    }
+}
+
 fn into_constraint_vec(array: &[(u8, &[Coord<7, 7>])]) -> Vec<Constraint<7, 7>> {
     array
         .iter()
@@ -199,7 +468,7 @@ fn generate_valid_constraints(
     let mut available_coords = Vec::new();
     for y in 0..10 {
         for x in 0..10 {
-            available_coords.push(Coord::<10, 10>(x as u8, y as u8));
+            available_coords.push(Coord::<10, 10>(x as u16, y as u16));
         }
     }
     while constraints.len() < constraint_amount {
@@ -0,0 +1,66 @@
+use miinaharava::minefield::{Cell, Coord, GenerationPolicy, Matrix, Minefield};
+
+use crate::ai::no_guess::{generate_no_guess, solves_without_guessing};
+
+#[test]
+fn test_generate_no_guess() {
+    for seed in 0..10 {
+        let mut next_seed = seed * 1000;
+        let found = generate_no_guess(
+            || {
+                next_seed += 1;
+                Minefield::<16, 16>::generate_with_policy(
+                    40,
+                    next_seed,
+                    GenerationPolicy::OpeningFirstClick,
+                )
+                .unwrap()
+            },
+            1000,
+        )
+        .unwrap();
+
+        assert_eq!(found.start, Coord(8, 8));
+        assert_eq!(
+            found.minefield.seed(),
+            Some(seed * 1000 + found.candidates as u64)
+        );
+        assert!(found
+            .minefield
+            .field
+            .iter()
+            .flatten()
+            .all(|cell| *cell == Cell::Hidden));
+        assert!(solves_without_guessing(&found.minefield, found.start));
+    }
+}
+
+#[test]
+fn should_reject_minefields_that_need_guessing() {
+    // After revealing the bottom right tile, the mine could be in any of the
+    // other three tiles
+    let mut mines = Matrix([[false; 2]; 2]);
+    mines.set(Coord(0, 0), true);
+    let minefield = Minefield::<2, 2>::with_mines(mines);
+    assert!(!solves_without_guessing(&minefield, Coord(1, 1)));
+    // Revealing a mine is not solving either
+    assert!(!solves_without_guessing(&minefield, Coord(0, 0)));
+
+    let mut candidates = 0;
+    let found = generate_no_guess(
+        || {
+            candidates += 1;
+            minefield.clone()
+        },
+        5,
+    );
+    assert!(found.is_none());
+    assert_eq!(candidates, 5);
+
+    // Revealing the center opens everything but the mine at the end
+    let mut mines = Matrix([[false; 5]; 1]);
+    mines.set(Coord(0, 0), true);
+    let minefield = Minefield::<5, 1>::with_mines(mines);
+    let found = generate_no_guess(|| minefield.clone(), 5).unwrap();
+    assert_eq!((found.start, found.candidates), (Coord(2, 0), 1));
+}
@@ -0,0 +1,198 @@
+use bitvec::prelude::*;
+use miinaharava::minefield::Coord;
+
+use crate::ai::{
+    backtracking::solutions::{PossibleSolution, SolutionList},
+    coord_set::CoordSet,
+    probabilities::MineProbabilities,
+};
+
+/// Two constraints `1` over (0, 0), (1, 0) and (1, 0), (2, 0) on the given
+/// row, so that either the middle one is a mine, or both of the others are.
+fn overlapping_ones(y: u16, remaining_mines: u16) -> SolutionList<7, 7> {
+    SolutionList::from(
+        vec![bitvec![0, 1, 0], bitvec![1, 0, 1]],
+        vec![Coord(0, y), Coord(1, y), Coord(2, y)],
+        remaining_mines,
+    )
+}
+
+/// Unconstrained variables on the last `amount` rows
+fn unconstrained(rows: u16) -> CoordSet<7, 7> {
+    let mut set = CoordSet::default();
+    for y in (7 - rows)..7 {
+        for x in 0..7 {
+            set.insert(Coord(x, y));
+        }
+    }
+    set
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1., |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// Calculate the probabilities by going through every combination of
+/// solutions
+fn brute_force(
+    lists: &[SolutionList<7, 7>],
+    unconstrained: usize,
+    remaining_mines: usize,
+) -> (Vec<(Coord<7, 7>, f64)>, f64) {
+    let solutions = lists
+        .iter()
+        .map(|l| {
+            l.iter()
+                .flatten()
+                .cloned()
+                .collect::<Vec<PossibleSolution>>()
+        })
+        .collect::<Vec<_>>();
+    let coords = lists
+        .iter()
+        .flat_map(|l| l.coords.clone())
+        .collect::<Vec<_>>();
+
+    let mut mine_weights = vec![0.; coords.len()];
+    let mut unconstrained_mines = 0.;
+    let mut total = 0.;
+    let mut indices = vec![0; lists.len()];
+    'combinations: loop {
+        let combination = indices
+            .iter()
+            .zip(&solutions)
+            .flat_map(|(i, s)| s[*i].iter().by_vals())
+            .collect::<Vec<_>>();
+        let mines = combination.iter().filter(|m| **m).count();
+        if mines <= remaining_mines && remaining_mines - mines <= unconstrained {
+            let weight = binomial(unconstrained, remaining_mines - mines);
+            total += weight;
+            unconstrained_mines += weight * (remaining_mines - mines) as f64;
+            for (idx, mine) in combination.iter().enumerate() {
+                if *mine {
+                    mine_weights[idx] += weight;
+                }
+            }
+        }
+
+        for (i, index) in indices.iter_mut().enumerate() {
+            *index += 1;
+            if *index < solutions[i].len() {
+                continue 'combinations;
+            }
+            *index = 0;
+        }
+        break;
+    }
+
+    (
+        coords
+            .into_iter()
+            .zip(mine_weights)
+            .map(|(c, w)| (c, w / total))
+            .collect(),
+        unconstrained_mines / total / unconstrained as f64,
+    )
+}
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+}
+
+/// The solution with less mines leaves more mines for the unconstrained
+/// variables, which can be placed in more ways, so it is more likely.
+#[test]
+fn test_solutions_are_weighted_by_unconstrained() {
+    let probabilities =
+        MineProbabilities::from(&[overlapping_ones(0, 3)], unconstrained(1), 3).unwrap();
+
+    // C(7, 2) = 21 ways for the middle mine, C(7, 1) = 7 for the other two
+    let expected = [
+        (Coord(0, 0), 7. / 28.),
+        (Coord(1, 0), 21. / 28.),
+        (Coord(2, 0), 7. / 28.),
+    ];
+    for ((coord, p), (expected_coord, expected_p)) in probabilities.constrained.iter().zip(expected)
+    {
+        assert_eq!(*coord, expected_coord);
+        assert_close(*p, expected_p);
+    }
+    assert_close(
+        probabilities.unconstrained_probability,
+        (21. * 2. + 7.) / 28. / 7.,
+    );
+    assert_eq!(
+        probabilities.safest_constrained().map(|(c, _)| c),
+        Some(Coord(0, 0))
+    );
+}
+
+/// Probabilities of several sets are the same as when going through every
+/// combination, and together they always add up to the remaining mines.
+#[test]
+fn test_probabilities_match_brute_force() {
+    for remaining_mines in 3..=12 {
+        for rows in 1..=3 {
+            let lists = [
+                overlapping_ones(0, remaining_mines),
+                overlapping_ones(1, remaining_mines),
+                SolutionList::from(
+                    vec![
+                        bitvec![1, 0, 0, 0],
+                        bitvec![1, 1, 1, 0],
+                        bitvec![0, 0, 0, 1],
+                    ],
+                    vec![Coord(0, 2), Coord(1, 2), Coord(2, 2), Coord(3, 2)],
+                    remaining_mines,
+                ),
+            ];
+            let unconstrained = unconstrained(rows);
+            let len = unconstrained.iter().count();
+            let probabilities =
+                MineProbabilities::from(&lists, unconstrained, remaining_mines).unwrap();
+            let (expected, expected_unconstrained) =
+                brute_force(&lists, len, remaining_mines as usize);
+
+            for ((coord, p), (expected_coord, expected_p)) in
+                probabilities.constrained.iter().zip(&expected)
+            {
+                assert_eq!(coord, expected_coord);
+                assert_close(*p, *expected_p);
+            }
+            assert_close(
+                probabilities.unconstrained_probability,
+                expected_unconstrained,
+            );
+
+            let total = probabilities
+                .constrained
+                .iter()
+                .map(|(_, p)| p)
+                .sum::<f64>()
+                + probabilities.unconstrained_probability * len as f64;
+            assert_close(total, remaining_mines as f64);
+        }
+    }
+}
+
+/// When there are no unconstrained variables, every solution must use all of
+/// the remaining mines.
+#[test]
+fn test_probabilities_without_unconstrained() {
+    let probabilities =
+        MineProbabilities::from(&[overlapping_ones(0, 2)], CoordSet::default(), 2).unwrap();
+    assert_eq!(
+        probabilities
+            .constrained
+            .iter()
+            .map(|(_, p)| *p)
+            .collect::<Vec<_>>(),
+        vec![1., 0., 1.]
+    );
+
+    // Not enough room for the mines
+    assert_eq!(
+        MineProbabilities::from(&[overlapping_ones(0, 3)], CoordSet::default(), 3),
+        None
+    );
+}
@@ -8,13 +8,20 @@
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+use ai::{CellContent, GuessStrategy};
 use argh::FromArgs;
 use miinaharava::{
     game::{Game, GameWindow},
-    sdl2::{event::Event, keyboard::Keycode},
+    minefield::{Coord, GameState, GenerationPolicy, Matrix, Topology, CUSTOM_MAX_SIZE},
+    sdl2::{event::Event, keyboard::Keycode, mouse::MouseButton, pixels::Color},
+};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use thread_controller::{
+    Difficulty, GuessStats, State, StateStats, StateWrapper, ThreadController,
 };
-use std::time::{Duration, Instant};
-use thread_controller::{Difficulty, GuessStats, StateStats, StateWrapper, ThreadController};
 
 mod ai;
 mod thread_controller;
@@ -29,12 +36,39 @@ struct VisualState<'a> {
     game: Game<'a>,
     /// Delay, which determines how long the AI will wait before acting again.
     delay: Duration,
+    /// The difficulty that is currently played.
+    difficulty: Difficulty,
+    /// The policy that new minefields are generated with.
+    policy: GenerationPolicy,
+    /// The topology of new minefields.
+    topology: Topology,
+    /// Whether only minefields that can be solved without guessing are
+    /// played.
+    no_guess: bool,
+    /// The directory where replays of finished games are saved, if any.
+    replays: Option<PathBuf>,
+    /// Whether the probabilities of the AI are shown over the minefield, see
+    /// [heatmap].
+    heatmap: bool,
 }
 
 impl<'a> VisualState<'a> {
     /// Reset the current state with the specified difficulty.
     pub fn reset_with_difficulty(&mut self, difficulty: Difficulty) {
-        *self.controller.state.lock().unwrap() = StateWrapper::from(difficulty);
+        *self.controller.state.lock().unwrap() = StateWrapper::new(
+            difficulty,
+            None,
+            self.policy,
+            self.topology,
+            self.no_guess,
+            self.replays.clone(),
+        );
+        self.controller
+            .state
+            .lock()
+            .unwrap()
+            .set_track_probabilities(self.heatmap);
+        self.difficulty = difficulty;
         self.game.timer = 0.;
     }
 
@@ -55,6 +89,16 @@ impl<'a> VisualState<'a> {
         self.game.timer_paused = self.controller.toggle_pause();
     }
 
+    /// Toggles showing the probabilities of the AI over the minefield
+    pub fn toggle_heatmap(&mut self) {
+        self.heatmap = !self.heatmap;
+        self.controller
+            .state
+            .lock()
+            .unwrap()
+            .set_track_probabilities(self.heatmap);
+    }
+
     /// Draws the actual minefield
     pub fn draw(&mut self) {
         let state = {
@@ -65,12 +109,38 @@ impl<'a> VisualState<'a> {
         self.draw_layout(&state.stats());
 
         match state {
-            StateWrapper::Easy(state) => self.game.draw(&state.minefield, None),
-            StateWrapper::Intermediate(state) => self.game.draw(&state.minefield, None),
-            StateWrapper::Expert(state) => self.game.draw(&state.minefield, None),
+            StateWrapper::Easy(state) => self.draw_state(&state),
+            StateWrapper::Intermediate(state) => self.draw_state(&state),
+            StateWrapper::Expert(state) => self.draw_state(&state),
+            StateWrapper::Custom(state) => self.draw_state(&state),
         }
     }
 
+    /// Draws the minefield of the given state, and the heatmap over it along
+    /// with the latest guess if the heatmap is shown.
+    fn draw_state<const W: usize, const H: usize>(&mut self, state: &State<W, H>) {
+        if !self.heatmap {
+            self.game.draw(&state.minefield, None);
+            return;
+        }
+        let marker = state.last_guess.map(|(coord, probability)| {
+            self.game.append_extra(
+                format!(
+                    "Last guess: {}, {} ({:.0}% safe)\n",
+                    coord.0,
+                    coord.1,
+                    probability.to_num::<f32>() * 100.
+                ),
+                None,
+                None,
+            );
+            coord
+        });
+        let overlay = heatmap(state);
+        self.game
+            .draw_overlaid(&state.minefield, None, marker, Some(&overlay));
+    }
+
     /// Draws necessary text on the extra layout for Game, such as keybinds and
     /// other useful information about the current game.
     fn draw_layout(&mut self, stats: &StateStats) {
@@ -79,6 +149,7 @@ impl<'a> VisualState<'a> {
         self.game.append_keybind("2", "Intermediate");
         self.game.append_keybind("3", "Expert");
         self.game.append_keybind("Space", "Toggle Pause");
+        self.game.append_keybind("H", "Toggle Heatmap");
         self.game
             .append_keybind("Up/Down", format!("Delay {:.1?}\n", self.delay));
 
@@ -107,67 +178,203 @@ impl<'a> VisualState<'a> {
 fn main() {
     let args: CommandLineArguments = argh::from_env();
     let difficulty = args.difficulty.unwrap_or(Difficulty::Easy);
-    let duration = args.seconds.map(|s| Duration::from_secs(s as u64));
-    let threads = args.threads.unwrap_or(1);
+    let policy = args.policy.unwrap_or_default();
+    let topology = args.topology.unwrap_or_default();
+
+    if let Some(path) = &args.export_board {
+        let state = StateWrapper::new(difficulty, args.seed, policy, topology, args.no_guess, None);
+        match state.export_board(path) {
+            Ok((x, y)) => {
+                println!("Saved the board to {}", path.display());
+                println!("Start by revealing the tile at {}, {}", x, y);
+                if args.no_guess {
+                    println!("Candidate boards generated: {}", state.stats().candidates);
+                }
+            }
+            Err(e) => eprintln!("Could not save the board: {:?}", e),
+        }
+        return;
+    }
 
     if args.headless {
-        let max_games = if duration.is_none() {
-            Some(args.games.unwrap_or(1000))
+        if args.compare_guesses {
+            let seed = args.seed.unwrap_or_else(rand::random);
+            let [safest, scored] = [GuessStrategy::Safest, GuessStrategy::Scored].map(|strategy| {
+                let (stats, _, _) = run_headless(&args, difficulty, strategy, Some(seed));
+                stats.iter().fold((0, 0), |games, stats| {
+                    (games.0 + stats.games.0, games.1 + stats.games.1)
+                })
+            });
+            print_comparison(difficulty, seed, safest, scored);
+            return;
+        }
+
+        let guess_strategy = if args.scored_guesses {
+            GuessStrategy::Scored
         } else {
-            args.games
-        };
-        let (mut stats, time) = {
-            let before = Instant::now();
-            let mut thread_controllers = Vec::with_capacity(threads as usize);
-            for _ in 0..threads {
-                thread_controllers.push(ThreadController::start(
-                    difficulty.into(),
-                    false,
-                    max_games,
-                ));
-            }
-            loop {
-                if let Some(max_games) = max_games {
-                    let mut total_games = 0;
-                    for controller in &thread_controllers {
-                        let stats = {
-                            let state = controller.state.lock().unwrap();
-                            state.stats()
-                        };
-                        total_games += stats.games.0 + stats.games.1;
-                    }
-                    if total_games >= max_games {
-                        break;
-                    }
-                    println!(" {} / {}", total_games, max_games);
-                }
-                if let Some(duration) = duration {
-                    let passed = Instant::now() - before;
-                    if passed >= duration {
-                        break;
-                    }
-                    println!(" Passed time: {:.1?}", passed);
-                }
-                std::thread::sleep(Duration::from_millis(100));
-            }
-            let mut stats = Vec::new();
-            for controller in thread_controllers {
-                let lock = controller.state.lock().unwrap();
-                stats.push(lock.stats());
-            }
-            (stats, Instant::now() - before)
+            GuessStrategy::default()
         };
+        let (mut stats, lost_seeds, time) =
+            run_headless(&args, difficulty, guess_strategy, args.seed);
+        let show_lost_seeds = args.show_lost_seeds;
         let stats = stats.iter_mut().reduce(|a, b| a.combine(b));
         if let Some(stats) = stats {
             stats.print(args, difficulty, time);
         }
+        if show_lost_seeds {
+            println!("\nSeeds of lost games:");
+            for seed in lost_seeds {
+                println!("  {}", seed);
+            }
+        }
     } else {
-        start_with_window(difficulty);
+        start_with_window(
+            difficulty,
+            args.seed,
+            (policy, topology, args.no_guess),
+            args.replays,
+        );
     }
 }
 
+/// Play games headlessly on as many threads as requested, until the requested
+/// amount of games have been played or the requested time has passed. Returns
+/// the stats and the lost seeds of every thread, and the time it took.
+fn run_headless(
+    args: &CommandLineArguments,
+    difficulty: Difficulty,
+    guess_strategy: GuessStrategy,
+    seed: Option<u64>,
+) -> (Vec<StateStats>, Vec<u64>, Duration) {
+    let duration = args.seconds.map(|s| Duration::from_secs(s as u64));
+    let max_games = if duration.is_none() {
+        Some(args.games.unwrap_or(1000))
+    } else {
+        args.games
+    };
+    let before = Instant::now();
+    let mut thread_controllers = Vec::with_capacity(args.threads.unwrap_or(1) as usize);
+    for i in 0..args.threads.unwrap_or(1) {
+        // Every thread gets its own range of seeds
+        let seed = seed.map(|s| s.wrapping_add((i as u64) << 32));
+        let mut state = StateWrapper::new(
+            difficulty,
+            seed,
+            args.policy.unwrap_or_default(),
+            args.topology.unwrap_or_default(),
+            args.no_guess,
+            args.replays.clone(),
+        );
+        state.set_guess_strategy(guess_strategy);
+        if let Some(size) = args.endgame_size {
+            state.set_endgame_size(size);
+        }
+        thread_controllers.push(ThreadController::start(state, false, max_games));
+    }
+    loop {
+        if let Some(max_games) = max_games {
+            let mut total_games = 0;
+            for controller in &thread_controllers {
+                let stats = {
+                    let state = controller.state.lock().unwrap();
+                    state.stats()
+                };
+                total_games += stats.games.0 + stats.games.1;
+            }
+            if total_games >= max_games {
+                break;
+            }
+            println!(" {} / {}", total_games, max_games);
+        }
+        if let Some(duration) = duration {
+            let passed = Instant::now() - before;
+            if passed >= duration {
+                break;
+            }
+            println!(" Passed time: {:.1?}", passed);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let mut stats = Vec::new();
+    let mut lost_seeds = Vec::new();
+    for controller in thread_controllers {
+        let lock = controller.state.lock().unwrap();
+        stats.push(lock.stats());
+        lost_seeds.extend_from_slice(lock.lost_seeds());
+    }
+    (stats, lost_seeds, Instant::now() - before)
+}
+
+/// Print the win rates of the same games played with both guess strategies,
+/// given as (victories, losses).
+fn print_comparison(difficulty: Difficulty, seed: u64, safest: (u32, u32), scored: (u32, u32)) {
+    let win_rate = |(victories, losses): (u32, u32)| {
+        victories as f32 / (victories + losses).max(1) as f32 * 100.
+    };
+    println!("-----------------");
+    println!("Guess strategy comparison:");
+    println!("Game difficulty: {:?}", difficulty);
+    println!("First seed: {}", seed);
+    for (name, games) in [("Safest", safest), ("Scored", scored)] {
+        println!(
+            "  {} guesses: {} / {} won ({:.2}%)",
+            name,
+            games.0,
+            games.0 + games.1,
+            win_rate(games)
+        );
+    }
+    println!(
+        "  Difference: {:+.2} percentage points",
+        win_rate(scored) - win_rate(safest)
+    );
+}
+
+/// Colour every hidden tile by how likely the AI thinks it is to be a mine,
+/// from green for certainly safe to red for certainly a mine. Tiles that the AI
+/// has already deduced but not acted on yet are blue if they are safe and
+/// purple if they are mines. Nothing is coloured once the game is over.
+fn heatmap<const W: usize, const H: usize>(state: &State<W, H>) -> Matrix<Option<Color>, W, H> {
+    let mut overlay = Matrix::from(None);
+    if state.minefield.game_state() != GameState::Pending {
+        return overlay;
+    }
+    for y in 0..state.minefield.height() {
+        for x in 0..state.minefield.width() {
+            let coord = Coord(x, y);
+            if !state.minefield.field.get(coord).is_hidden() {
+                continue;
+            }
+            let color = match (
+                state.csp_state.known_fields.get(coord),
+                &state.probabilities,
+            ) {
+                (CellContent::Known(false), _) => Color::RGBA(0x20, 0x60, 0xFF, 0xA0),
+                (CellContent::Known(true), _) => Color::RGBA(0xA0, 0x20, 0xE0, 0xA0),
+                (CellContent::Unknown, Some(probabilities)) => {
+                    let probability = probabilities.get(coord).clamp(0., 1.);
+                    Color::RGBA(
+                        (probability * 255.) as u8,
+                        ((1. - probability) * 255.) as u8,
+                        0,
+                        0x80,
+                    )
+                }
+                (CellContent::Unknown, None) => continue,
+            };
+            overlay.set(coord, Some(color));
+        }
+    }
+    overlay
+}
+
 /// Start the program with a visual interface for a neat empiric feel.
-fn start_with_window(difficulty: Difficulty) {
+fn start_with_window(
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    (policy, topology, no_guess): (GenerationPolicy, Topology, bool),
+    replays: Option<PathBuf>,
+) {
     let mut window = GameWindow::start();
     let mut game = Game::init(&mut window);
 
@@ -175,28 +382,56 @@ fn start_with_window(difficulty: Difficulty) {
     game.timer_paused = false;
     game.extra_layout_default_size = 25.;
     let mut state = VisualState {
-        controller: ThreadController::start(difficulty.into(), game.timer_paused, None),
+        controller: ThreadController::start(
+            StateWrapper::new(
+                difficulty,
+                seed,
+                policy,
+                topology,
+                no_guess,
+                replays.clone(),
+            ),
+            game.timer_paused,
+            None,
+        ),
         delay: Duration::from_millis(25),
+        difficulty,
         game,
+        policy,
+        topology,
+        no_guess,
+        replays,
+        heatmap: false,
     };
     state.controller.set_delay(Some(state.delay));
 
     while let Some(events) = state.game.update() {
         for event in events.events {
-            if let Event::KeyDown {
-                keycode: Some(c), ..
-            } = event
-            {
-                use Difficulty::*;
-                match c {
-                    Keycode::Num1 => state.reset_with_difficulty(Easy),
-                    Keycode::Num2 => state.reset_with_difficulty(Intermediate),
-                    Keycode::Num3 => state.reset_with_difficulty(Expert),
-                    Keycode::Up => state.add_delay(Duration::from_millis(1)),
-                    Keycode::Down => state.sub_delay(Duration::from_millis(1)),
-                    Keycode::Space => state.toggle_pause(),
-                    _ => (),
+            match event {
+                Event::KeyDown {
+                    keycode: Some(c), ..
+                } => {
+                    use Difficulty::*;
+                    match c {
+                        Keycode::Num1 => state.reset_with_difficulty(Easy),
+                        Keycode::Num2 => state.reset_with_difficulty(Intermediate),
+                        Keycode::Num3 => state.reset_with_difficulty(Expert),
+                        Keycode::Up => state.add_delay(Duration::from_millis(1)),
+                        Keycode::Down => state.sub_delay(Duration::from_millis(1)),
+                        Keycode::Space => state.toggle_pause(),
+                        Keycode::H => state.toggle_heatmap(),
+                        _ => (),
+                    }
+                }
+                Event::MouseButtonUp {
+                    mouse_btn: MouseButton::Left,
+                    x,
+                    y,
+                    ..
+                } if state.game.is_reset_button((x, y)) => {
+                    state.reset_with_difficulty(state.difficulty)
                 }
+                _ => (),
             }
         }
 
@@ -211,7 +446,7 @@ struct CommandLineArguments {
     #[argh(switch, short = 'h')]
     headless: bool,
 
-    /// difficulty of the game, easy by default
+    /// difficulty of the game, either 'easy', 'intermediate', 'expert' or a custom size as '<width>x<height>:<mines>'. Easy by default.
     #[argh(option, from_str_fn(difficulty_from_str), short = 'd')]
     difficulty: Option<Difficulty>,
 
@@ -230,6 +465,46 @@ struct CommandLineArguments {
     /// number of threads used to run simultaneous games, affects only headless mode. Defaults to 1.
     #[argh(option, short = 't')]
     threads: Option<u32>,
+
+    /// seed for the first minefield, following minefields use the following seeds. Every thread starts from its own seed.
+    #[argh(option)]
+    seed: Option<u64>,
+
+    /// show the seeds of all the lost games once done, affects only headless mode.
+    #[argh(switch)]
+    show_lost_seeds: bool,
+
+    /// how mines are placed in relation to the first reveal, either 'random', 'safe', 'opening' or 'classic'. Random by default.
+    #[argh(option, short = 'p')]
+    policy: Option<GenerationPolicy>,
+
+    /// which tiles are neighbours, either 'moore', 'torus', 'orthogonal', 'knight' or 'hex'. Moore (the 8 surrounding tiles) by default.
+    #[argh(option)]
+    topology: Option<Topology>,
+
+    /// directory where the replays of all finished games are saved, not saved by default
+    #[argh(option)]
+    replays: Option<PathBuf>,
+
+    /// only play minefields that the AI can solve without guessing from the center of the minefield
+    #[argh(switch)]
+    no_guess: bool,
+
+    /// play the same games with both the safest guesses and the scored guesses that also weigh how much a guess reveals, and compare their win rates, affects only headless mode
+    #[argh(switch)]
+    compare_guesses: bool,
+
+    /// guess the tile that is likely to reveal the most out of the tiles that are nearly as safe as the safest one, instead of the safest tile, affects only headless mode
+    #[argh(switch)]
+    scored_guesses: bool,
+
+    /// the largest amount of hidden tiles for which the AI searches every possible outcome for the move most likely to win, 0 never searches. 16 by default, affects only headless mode
+    #[argh(option)]
+    endgame_size: Option<usize>,
+
+    /// save a single minefield as an MBF board to the given path and exit, which can then be played with 'miinaharava --board'
+    #[argh(option)]
+    export_board: Option<PathBuf>,
 }
 
 /// Try to parse difficulty from string
@@ -238,7 +513,32 @@ fn difficulty_from_str(value: &str) -> Result<Difficulty, String> {
         "easy" => Difficulty::Easy,
         "intermediate" | "med" | "medium" => Difficulty::Intermediate,
         "expert" | "ex" | "hard" => Difficulty::Expert,
-        _ => Err("difficulty must be either 'easy', 'intermediate' or 'expert'")?,
+        custom => {
+            let error = format!(
+                "difficulty must be either 'easy', 'intermediate', 'expert' or '<width>x<height>:<mines>' with a size of at most {0}x{0}",
+                CUSTOM_MAX_SIZE
+            );
+            let (size, mines) = custom.split_once(':').ok_or(&error)?;
+            let (width, height) = size.split_once('x').ok_or(&error)?;
+            let (width, height, mines) = match (width.parse(), height.parse(), mines.parse()) {
+                (Ok(width), Ok(height), Ok(mines)) => (width, height, mines),
+                _ => Err(&error)?,
+            };
+            if width == 0
+                || height == 0
+                || width as usize > CUSTOM_MAX_SIZE
+                || height as usize > CUSTOM_MAX_SIZE
+            {
+                Err(&error)?
+            } else if mines as usize >= width as usize * height as usize {
+                Err("there must be fewer mines than there are tiles")?
+            }
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            }
+        }
     })
 }
 
@@ -252,6 +552,7 @@ impl StateStats {
         println!("-----------------");
         println!("Statistics:");
         println!("Game difficulty: {:?}", difficulty);
+        println!("Generation policy: {:?}", args.policy.unwrap_or_default());
 
         println!(
             "\n  Total time spent: {:.1?} (x {} thread(s))",
@@ -271,6 +572,29 @@ impl StateStats {
         println!("\n  Total games played: {}", self.games.0 + self.games.1);
         println!("    Victories: {}, ({}%)", self.games.0, vic_perc);
         println!("    Losses: {}, ({}%)", self.games.1, loss_perc);
+        println!(
+            "    Average clicks: {:.2}",
+            self.clicks as f32 / total_games as f32
+        );
+        if args.no_guess {
+            println!(
+                "    Average candidate boards: {:.2}",
+                self.candidates as f32 / total_games as f32
+            );
+        }
+
+        println!("\n  Win rates by board difficulty (3BV per safe tile):");
+        for (i, (victories, losses)) in self.difficulty_stats.iter().enumerate() {
+            if victories + losses > 0 {
+                println!(
+                    "    ~{}-{}%: {} games, {:.1}% won",
+                    i * 10,
+                    (i + 1) * 10,
+                    victories + losses,
+                    (*victories as f32 / (victories + losses) as f32) * 100.
+                );
+            }
+        }
 
         let mut clone = self.guess_stats;
         let total_guesses = clone.iter_mut().reduce(|a, b| a.combine(b));
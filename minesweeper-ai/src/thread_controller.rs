@@ -3,6 +3,7 @@
 //! program is shipped and run with.
 
 use std::{
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
@@ -11,9 +12,25 @@ use std::{
     time::{Duration, Instant},
 };
 
-use miinaharava::minefield::{GameState, Minefield, Reveal};
+use fixed::{types::extra::U20, FixedU32};
+use miinaharava::{
+    history::Action,
+    minefield::{
+        Coord, GameState, GenerationPolicy, Matrix, Minefield, MinefieldError, Reveal, Topology,
+        CUSTOM_MAX_SIZE,
+    },
+    replay::Replay,
+};
+
+use crate::{
+    ai::no_guess::generate_no_guess,
+    ai::{CSPState, Decision, GuessStrategy},
+};
 
-use crate::{ai::CSPState, ai::Decision};
+/// How many candidate minefields are generated for a single game without
+/// guessing between the warnings that none of them could be solved without
+/// guessing yet.
+const MAX_CANDIDATES: u32 = 1000;
 
 /// Macro that is useful for measuring how long a certain expression took.
 macro_rules! measure {
@@ -33,6 +50,16 @@ pub enum Difficulty {
     Intermediate,
     /// 30x16 field with 99 mines
     Expert,
+    /// Field of any size up to [CUSTOM_MAX_SIZE] in both directions, with any
+    /// amount of mines
+    Custom {
+        /// Width of the field
+        width: u16,
+        /// Height of the field
+        height: u16,
+        /// Amount of mines in the field
+        mines: u16,
+    },
 }
 
 /// Controller for the Ai's [State], which continually plays games by the AI.
@@ -130,6 +157,9 @@ pub enum StateWrapper {
     Intermediate(State<16, 16>),
     /// Represents a state for the Expert-difficulty (30x16)
     Expert(State<30, 16>),
+    /// Represents a state for a custom difficulty, of any size up to
+    /// [CUSTOM_MAX_SIZE]
+    Custom(State<CUSTOM_MAX_SIZE, CUSTOM_MAX_SIZE>),
 }
 
 impl StateWrapper {
@@ -140,6 +170,7 @@ impl StateWrapper {
             StateWrapper::Easy(s) => s.process(super_speed),
             StateWrapper::Intermediate(s) => s.process(super_speed),
             StateWrapper::Expert(s) => s.process(super_speed),
+            StateWrapper::Custom(s) => s.process(super_speed),
         }
     }
 
@@ -150,17 +181,110 @@ impl StateWrapper {
             StateWrapper::Easy(s) => s.stats,
             StateWrapper::Intermediate(s) => s.stats,
             StateWrapper::Expert(s) => s.stats,
+            StateWrapper::Custom(s) => s.stats,
+        }
+    }
+
+    /// Returns the seeds of all the minefields that have been lost so far,
+    /// convenience function to avoid having to match generics.
+    pub fn lost_seeds(&self) -> &[u64] {
+        match self {
+            StateWrapper::Easy(s) => &s.lost_seeds,
+            StateWrapper::Intermediate(s) => &s.lost_seeds,
+            StateWrapper::Expert(s) => &s.lost_seeds,
+            StateWrapper::Custom(s) => &s.lost_seeds,
+        }
+    }
+
+    /// Sets whether the probabilities of every cell being a mine are calculated
+    /// after every ponder, convenience function to avoid having to match
+    /// generics, see [State::track_probabilities].
+    pub fn set_track_probabilities(&mut self, track: bool) {
+        match self {
+            StateWrapper::Easy(s) => s.track_probabilities = track,
+            StateWrapper::Intermediate(s) => s.track_probabilities = track,
+            StateWrapper::Expert(s) => s.track_probabilities = track,
+            StateWrapper::Custom(s) => s.track_probabilities = track,
+        }
+    }
+
+    /// Sets how the AI chooses where to guess, convenience function to avoid
+    /// having to match generics.
+    pub fn set_guess_strategy(&mut self, strategy: GuessStrategy) {
+        match self {
+            StateWrapper::Easy(s) => s.csp_state.guess_strategy = strategy,
+            StateWrapper::Intermediate(s) => s.csp_state.guess_strategy = strategy,
+            StateWrapper::Expert(s) => s.csp_state.guess_strategy = strategy,
+            StateWrapper::Custom(s) => s.csp_state.guess_strategy = strategy,
+        }
+    }
+
+    /// Sets the largest amount of hidden cells for which the AI searches the
+    /// endgame exhaustively, convenience function to avoid having to match
+    /// generics, see [CSPState::endgame_size].
+    pub fn set_endgame_size(&mut self, size: usize) {
+        match self {
+            StateWrapper::Easy(s) => s.csp_state.endgame_size = size,
+            StateWrapper::Intermediate(s) => s.csp_state.endgame_size = size,
+            StateWrapper::Expert(s) => s.csp_state.endgame_size = size,
+            StateWrapper::Custom(s) => s.csp_state.endgame_size = size,
+        }
+    }
+
+    /// Saves the current minefield as an MBF board, convenience function to
+    /// avoid having to match generics, see [State::export_board].
+    pub fn export_board(&self, path: &Path) -> Result<(u16, u16), MinefieldError> {
+        match self {
+            StateWrapper::Easy(s) => s.export_board(path),
+            StateWrapper::Intermediate(s) => s.export_board(path),
+            StateWrapper::Expert(s) => s.export_board(path),
+            StateWrapper::Custom(s) => s.export_board(path),
+        }
+    }
+
+    /// Creates a new state for the given difficulty. If a seed is given, the
+    /// minefields are generated from consecutive seeds starting from it. If
+    /// `no_guess` is set, only minefields that can be solved without guessing
+    /// are played. If a directory for replays is given, the replays of all
+    /// finished games are saved there.
+    pub fn new(
+        difficulty: Difficulty,
+        seed: Option<u64>,
+        policy: GenerationPolicy,
+        topology: Topology,
+        no_guess: bool,
+        replays: Option<PathBuf>,
+    ) -> Self {
+        let settings = (policy, topology, no_guess);
+        match difficulty {
+            Difficulty::Easy => {
+                StateWrapper::Easy(State::new((10, 10), 10, seed, settings, replays))
+            }
+            Difficulty::Intermediate => {
+                StateWrapper::Intermediate(State::new((16, 16), 40, seed, settings, replays))
+            }
+            Difficulty::Expert => {
+                StateWrapper::Expert(State::new((30, 16), 99, seed, settings, replays))
+            }
+            Difficulty::Custom {
+                width,
+                height,
+                mines,
+            } => StateWrapper::Custom(State::new((width, height), mines, seed, settings, replays)),
         }
     }
 }
 
 impl From<Difficulty> for StateWrapper {
     fn from(value: Difficulty) -> Self {
-        match value {
-            Difficulty::Easy => StateWrapper::Easy(State::new(10)),
-            Difficulty::Intermediate => StateWrapper::Intermediate(State::new(40)),
-            Difficulty::Expert => StateWrapper::Expert(State::new(99)),
-        }
+        StateWrapper::new(
+            value,
+            None,
+            GenerationPolicy::default(),
+            Topology::default(),
+            false,
+            None,
+        )
     }
 }
 
@@ -178,7 +302,34 @@ pub struct State<const W: usize, const H: usize> {
     /// given for the AI to process.
     reveals: Vec<Reveal<W, H>>,
     /// Represents the state of the CSP-solver AI
-    csp_state: CSPState<W, H>,
+    pub csp_state: CSPState<W, H>,
+    /// Whether [State::probabilities] are calculated after every ponder. Only
+    /// needed for showing them, so off by default.
+    pub track_probabilities: bool,
+    /// The probability of every cell being a mine after the latest ponder, if
+    /// tracked, see [CSPState::mine_probabilities].
+    pub probabilities: Option<Matrix<f32, W, H>>,
+    /// The latest guess of the current game, and the probability of it being
+    /// safe.
+    pub last_guess: Option<(Coord<W, H>, FixedU32<U20>)>,
+    /// The seed that the next minefield is generated from, if any. Otherwise
+    /// minefields are generated from random seeds.
+    next_seed: Option<u64>,
+    /// Seeds of all the minefields that have been lost so far.
+    lost_seeds: Vec<u64>,
+    /// The policy all of the minefields are generated with.
+    policy: GenerationPolicy,
+    /// The topology of all of the minefields.
+    topology: Topology,
+    /// Whether only minefields that can be solved without guessing are
+    /// played.
+    no_guess: bool,
+    /// The actual width and height of the minefields, at most `W` and `H`.
+    size: (u16, u16),
+    /// Recording of the current game.
+    replay: Replay<W, H>,
+    /// The directory where replays of finished games are saved, if any.
+    replays: Option<PathBuf>,
 }
 
 /// The common statistics from a State, that are not bound by generics.
@@ -186,7 +337,7 @@ pub struct State<const W: usize, const H: usize> {
 pub struct StateStats {
     /// How many mines are in the current game state (re-used when regenerating
     /// minefield)
-    pub mines: u8,
+    pub mines: u16,
     /// How many games have been finished (Victories, Losses)
     pub games: (u32, u32),
     /// How much time has the AI spent [ponder]ing
@@ -195,8 +346,17 @@ pub struct StateStats {
     pub generation_time: Duration,
     /// How much time has been spent revealing or flagging tiles.
     pub decision_time: Duration,
+    /// How many times tiles have been revealed, flagged or chorded, not
+    /// counting the actions that changed nothing.
+    pub clicks: u32,
+    /// How many candidate minefields have been generated for games without
+    /// guessing, see [crate::ai::no_guess].
+    pub candidates: u32,
     /// A bracket for every 10th percentage level of guesses
     pub guess_stats: [GuessStats; 10],
+    /// Games (Victories, Losses) in a bracket for every 10th percentage level
+    /// of 3BV per safe tile, see [miinaharava::analysis].
+    pub difficulty_stats: [(u32, u32); 10],
 }
 
 impl StateStats {
@@ -207,9 +367,19 @@ impl StateStats {
         self.ai_time += other.ai_time;
         self.generation_time += other.generation_time;
         self.decision_time += other.decision_time;
+        self.clicks += other.clicks;
+        self.candidates += other.candidates;
         for (stat1, stat2) in self.guess_stats.iter_mut().zip(other.guess_stats.iter()) {
             stat1.combine(stat2);
         }
+        for (stat1, stat2) in self
+            .difficulty_stats
+            .iter_mut()
+            .zip(other.difficulty_stats.iter())
+        {
+            stat1.0 += stat2.0;
+            stat1.1 += stat2.1;
+        }
         self
     }
 }
@@ -221,9 +391,9 @@ pub struct GuessStats {
     pub amount_of_guesses: u32,
     /// The amount of guesses that have been successful
     pub successful_guesses: u32,
-    /// The average guess propability
+    /// The average guess probability
     pub average_guess: f32,
-    /// Total guess propabilities, used to calculate average guess.
+    /// Total guess probabilities, used to calculate average guess.
     total_guess_probabilities: f32,
 }
 
@@ -239,20 +409,123 @@ impl GuessStats {
 }
 
 impl<const W: usize, const H: usize> State<W, H> {
-    /// Creates a new state, only plays a certain difficulty.
-    pub fn new(mine_count: u8) -> State<W, H> {
+    /// Creates a new state, only plays a certain difficulty. If a seed is
+    /// given, every minefield is generated from the seed following the seed of
+    /// the previous minefield, so the games can be reproduced. The settings
+    /// are the policy and the topology of the minefields, and whether only
+    /// minefields that can be solved without guessing are played. If a
+    /// directory for replays is given, the replays of all finished games are
+    /// saved there.
+    pub fn new(
+        size: (u16, u16),
+        mine_count: u16,
+        seed: Option<u64>,
+        (policy, topology, no_guess): (GenerationPolicy, Topology, bool),
+        replays: Option<PathBuf>,
+    ) -> State<W, H> {
+        let mut next_seed = seed;
+        let (minefield, decisions, candidates) = State::generate_game(
+            size,
+            mine_count,
+            &mut next_seed,
+            (policy, topology, no_guess),
+        );
         State {
-            minefield: Minefield::generate(mine_count).unwrap(),
             stats: StateStats {
                 mines: mine_count,
+                candidates,
                 ..Default::default()
             },
-            decisions: Vec::new(),
+            decisions,
             reveals: Vec::new(),
-            csp_state: CSPState::default(),
+            csp_state: CSPState::new(&minefield),
+            track_probabilities: false,
+            probabilities: None,
+            last_guess: None,
+            replay: Replay::new(&minefield),
+            minefield,
+            next_seed,
+            lost_seeds: Vec::new(),
+            policy,
+            topology,
+            no_guess,
+            size,
+            replays,
         }
     }
 
+    /// Generate the minefield for the next game with
+    /// [State::generate_minefield]. If only minefields without guessing are
+    /// played, candidate minefields are generated until one of them can be
+    /// solved without guessing, in which case the decision to reveal its
+    /// starting tile is returned as well. Candidates are generated for as long
+    /// as it takes, so a minefield that might need guessing is never played
+    /// instead. Also returns how many candidates were generated.
+    fn generate_game(
+        size: (u16, u16),
+        mine_count: u16,
+        next_seed: &mut Option<u64>,
+        (policy, topology, no_guess): (GenerationPolicy, Topology, bool),
+    ) -> (Minefield<W, H>, Vec<Decision<W, H>>, u32) {
+        let mut candidate =
+            || State::generate_minefield(size, mine_count, next_seed, (policy, topology));
+        if !no_guess {
+            return (candidate(), Vec::new(), 0);
+        }
+        let mut candidates = 0;
+        loop {
+            if let Some(found) = generate_no_guess(&mut candidate, MAX_CANDIDATES) {
+                return (
+                    found.minefield,
+                    vec![Decision::Reveal(found.start)],
+                    candidates + found.candidates,
+                );
+            }
+            candidates += MAX_CANDIDATES;
+            eprintln!(
+                "None of the {} candidate minefields could be solved without guessing, still trying",
+                candidates
+            );
+        }
+    }
+
+    /// Generate the next minefield with the given policy and topology, either
+    /// from the given seed (which is then advanced) or from a random seed.
+    fn generate_minefield(
+        (width, height): (u16, u16),
+        mine_count: u16,
+        next_seed: &mut Option<u64>,
+        (policy, topology): (GenerationPolicy, Topology),
+    ) -> Minefield<W, H> {
+        let seed = if let Some(seed) = next_seed.as_mut() {
+            let current = *seed;
+            *seed = seed.wrapping_add(1);
+            current
+        } else {
+            rand::random()
+        };
+        let mut minefield =
+            Minefield::generate_sized(width, height, mine_count, seed, policy).unwrap();
+        minefield.set_topology(topology);
+        minefield
+    }
+
+    /// Save the current minefield as an MBF board, returning the coordinate of
+    /// the tile that should be revealed first. As the mines might only be
+    /// placed on the first reveal, that tile is revealed before saving. It is
+    /// the starting tile of a minefield without guessing, and otherwise the
+    /// center of the minefield.
+    pub fn export_board(&self, path: &Path) -> Result<(u16, u16), MinefieldError> {
+        let start = match self.decisions.last() {
+            Some(Decision::Reveal(coord)) => *coord,
+            _ => Coord(self.size.0 / 2, self.size.1 / 2),
+        };
+        let mut minefield = self.minefield.clone();
+        minefield.reveal(start)?;
+        minefield.save_mbf(path)?;
+        Ok((start.0, start.1))
+    }
+
     /// 1. If game already over, generate a new map
     /// 2. If there are no [Decision]s left, [ponder] and measure the time
     /// 3. Act on the next [Decision] (multiple if super_speed is on)
@@ -260,33 +533,70 @@ impl<const W: usize, const H: usize> State<W, H> {
         if self.minefield.game_state() != GameState::Pending {
             match self.minefield.game_state() {
                 GameState::Victory => self.stats.games.0 += 1,
-                GameState::GameOver => self.stats.games.1 += 1,
+                GameState::GameOver => {
+                    self.stats.games.1 += 1;
+                    self.lost_seeds.extend(self.minefield.seed());
+                }
                 _ => {}
             }
-            let (minefield, time) = measure!(Minefield::generate(self.stats.mines).unwrap());
+            if let Ok(bbbv) = self.minefield.bbbv() {
+                let (width, height) = (self.minefield.width(), self.minefield.height());
+                let safe_tiles = width as u32 * height as u32 - self.minefield.mines as u32;
+                let bracket = (bbbv * 10 / safe_tiles.max(1)).min(9) as usize;
+                let games = &mut self.stats.difficulty_stats[bracket];
+                match self.minefield.game_state() {
+                    GameState::Victory => games.0 += 1,
+                    _ => games.1 += 1,
+                }
+            }
+            if let Some(dir) = &self.replays {
+                let path = dir.join(self.replay.file_name(self.minefield.game_state()));
+                if let Err(e) = self.replay.save(&path) {
+                    eprintln!("Could not save the replay to {}: {:?}", path.display(), e);
+                }
+            }
+            let ((minefield, decisions, candidates), time) = measure!(State::generate_game(
+                self.size,
+                self.stats.mines,
+                &mut self.next_seed,
+                (self.policy, self.topology, self.no_guess)
+            ));
             self.minefield = minefield;
             self.stats.generation_time += time;
-            self.decisions.clear();
+            self.stats.candidates += candidates;
+            self.decisions = decisions;
             self.reveals.clear();
-            self.csp_state = CSPState::default();
+            self.csp_state = CSPState {
+                guess_strategy: self.csp_state.guess_strategy,
+                endgame_size: self.csp_state.endgame_size,
+                ..CSPState::new(&self.minefield)
+            };
+            self.probabilities = None;
+            self.last_guess = None;
+            self.replay = Replay::new(&self.minefield);
         } else if self.decisions.is_empty() {
             let (decisions, time) = measure!(self
                 .csp_state
                 .ponder(self.reveals.drain(..).collect(), &self.minefield));
             self.stats.ai_time += time;
             self.decisions = decisions;
+            if self.track_probabilities {
+                self.probabilities = self.csp_state.mine_probabilities(&self.minefield);
+            }
         }
         while let Some(decision) = self.decisions.pop() {
             let (_, time) = measure!({
                 if let Some(reveals) = match decision {
                     Decision::Reveal(coord) => self.minefield.reveal(coord).ok(),
                     Decision::Flag(coord) => self.minefield.flag(coord).ok(),
-                    Decision::GuessReveal(coord, propability) => {
+                    Decision::Chord(coord) => self.minefield.chord(coord).ok(),
+                    Decision::GuessReveal(coord, probability) => {
+                        self.last_guess = Some((coord, probability));
                         let guess_stats = &mut self.stats.guess_stats
-                            [((propability.to_num::<f32>() * 10.).floor() as usize).min(9)];
+                            [((probability.to_num::<f32>() * 10.).floor() as usize).min(9)];
 
                         guess_stats.amount_of_guesses += 1;
-                        guess_stats.total_guess_probabilities += propability.to_num::<f32>();
+                        guess_stats.total_guess_probabilities += probability.to_num::<f32>();
                         let res = self.minefield.reveal(coord).ok();
                         if res.is_some() && self.minefield.game_state() != GameState::GameOver {
                             guess_stats.successful_guesses += 1;
@@ -296,7 +606,21 @@ impl<const W: usize, const H: usize> State<W, H> {
                         res
                     }
                 } {
+                    // Like the wasted clicks of humans, actions that change
+                    // nothing are not counted as clicks
+                    if !reveals.is_empty() {
+                        self.stats.clicks += 1;
+                    }
                     self.reveals.extend(reveals);
+                    if self.replays.is_some() {
+                        self.replay.record(match decision {
+                            Decision::Flag(coord) => Action::Flag(coord),
+                            Decision::Chord(coord) => Action::Chord(coord),
+                            Decision::Reveal(coord) | Decision::GuessReveal(coord, _) => {
+                                Action::Reveal(coord)
+                            }
+                        });
+                    }
                 }
             });
             self.stats.decision_time += time;